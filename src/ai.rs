@@ -1,10 +1,12 @@
 use crate::creature::action::Action;
 use crate::creature::perception::{Awareness, Perception};
+use crate::creature::reaction::Reaction;
 use crate::world::Layer;
+use serde::{Deserialize, Serialize};
 
 mod wander;
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Deserialize, PartialEq, Serialize)]
 pub enum Behavior {
     PlayerControlled(u128),
     Inactive,
@@ -79,3 +81,18 @@ pub fn act(actor: &AI, layer: &Layer) -> Action {
         Behavior::Wandering => wander::act(actor, layer),
     }
 }
+
+// Returns true if the creature takes the reaction. Creatures that are
+// doing nothing don't react either, everyone else takes every chance.
+// Players answer their own prompts, so the AI never reacts for them.
+pub fn react(actor: &AI, _reaction: &Reaction, _layer: &Layer) -> bool {
+    let actor_behavior = match actor.perception.get_awareness() {
+        Awareness::Wander => actor.get_wander_behavior(),
+        Awareness::Combat => actor.get_combat_behavior(),
+    };
+
+    match actor_behavior {
+        Behavior::PlayerControlled(_) | Behavior::Inactive => false,
+        Behavior::Wandering => true,
+    }
+}
@@ -20,7 +20,7 @@ pub fn act(actor: &AI, layer: &Layer) -> Action {
 
     #[rustfmt::skip]
     let all_paths = gridalgos::find_all_shortest_paths(
-        &vec![cur_position],
+        &[cur_position],
         movement_speed,
         &layer,
     );
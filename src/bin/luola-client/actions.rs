@@ -2,12 +2,16 @@ use crate::input::Direction;
 use crate::network;
 use crate::ui::UI;
 use crate::GameState;
-use luola::creature::action::{Action, MoveAction, UseItemAction};
+use luola::creature::action::{
+    Action, DigAction, DisarmAction, DropAction, EquipAction, InteractAction, LootAction,
+    MoveAction, PickUpAction, StabilizeAction, SwapSlotsAction, UseItemAction,
+};
 use luola::grid::GridSquare;
 use luola::info_message::MessageType;
 use luola::item::ItemKind;
-use luola::messages::Message;
+use luola::messages::{Message, ReactMsg};
 use std::sync::mpsc::Sender;
+use std::time::Instant;
 
 pub fn move_selection(direction: Direction, state: &mut GameState) {
     let delta = match direction {
@@ -19,8 +23,11 @@ pub fn move_selection(direction: Direction, state: &mut GameState) {
 
     state.ui.viewport.move_selection(delta);
 
+    // Living creatures are shown before downed creatures and corpses
     let selected_square = state.ui.viewport.get_selected_world_square();
-    let creatures_at = state.layer.get_living_creatures_at(selected_square);
+    let mut creatures_at = state.layer.get_living_creatures_at(selected_square);
+    creatures_at.append(&mut state.layer.get_downed_creatures_at(selected_square));
+    creatures_at.append(&mut state.layer.get_dead_creatures_at(selected_square));
     if !creatures_at.is_empty() {
         state.ui.set_displayed_creature(creatures_at[0]);
     } else {
@@ -55,7 +62,7 @@ pub fn use_item(outgoing_tx: &Sender<Message>, state: &mut GameState) {
 
     let target = match item.kind {
         ItemKind::Active(_) => state.ui.viewport.get_selected_world_square(),
-        ItemKind::Passive => {
+        ItemKind::Passive(_) => {
             let error_msg = MessageType::Error(String::from("Can't activate passive items"));
             state.ui.message_log.add_message(error_msg);
             return;
@@ -82,3 +89,245 @@ pub fn move_creature(outgoing_tx: &Sender<Message>, state: &GameState) {
 
     network::send_message(outgoing_tx, msg);
 }
+
+// Interact with the selected square, for example to open a door
+pub fn interact(outgoing_tx: &Sender<Message>, state: &GameState) {
+    let target = state.ui.viewport.get_selected_world_square();
+
+    let msg = Message::Act(Action::Interact(InteractAction { target }));
+    network::send_message(outgoing_tx, msg);
+}
+
+// Try to disarm a found trap at the selected square
+pub fn disarm_trap(outgoing_tx: &Sender<Message>, state: &GameState) {
+    let target = state.ui.viewport.get_selected_world_square();
+
+    let msg = Message::Act(Action::Disarm(DisarmAction { target }));
+    network::send_message(outgoing_tx, msg);
+}
+
+// Dig into the wall or door at the selected square
+pub fn dig(outgoing_tx: &Sender<Message>, state: &GameState) {
+    let target = state.ui.viewport.get_selected_world_square();
+
+    let msg = Message::Act(Action::Dig(DigAction { target }));
+    network::send_message(outgoing_tx, msg);
+}
+
+// Tend to the dying creature at the selected square
+pub fn stabilize(outgoing_tx: &Sender<Message>, state: &GameState) {
+    let target = state.ui.viewport.get_selected_world_square();
+
+    let msg = Message::Act(Action::Stabilize(StabilizeAction { target }));
+    network::send_message(outgoing_tx, msg);
+}
+
+// Pick up the first item on the acting creature's square, into the
+// selected inventory slot if it is empty and otherwise into the first
+// empty slot.
+pub fn pick_up_item(outgoing_tx: &Sender<Message>, state: &mut GameState) {
+    let acting_creature_id = state
+        .acting_creature
+        .expect("it should be the player's turn");
+    let acting_creature = state.layer.creatures.get(&acting_creature_id).unwrap();
+    let inventory = &acting_creature.inventory;
+
+    let items_at = state.layer.get_items_at(acting_creature.get_position());
+    if items_at.is_empty() {
+        let error_msg = MessageType::Error(String::from("There is nothing here to pick up"));
+        state.ui.message_log.add_message(error_msg);
+        return;
+    }
+
+    let inventory_slot = match state.ui.inventory_info.get_selected_slot() {
+        Some(slot) if inventory.valid_slot(slot) && inventory.get_item(slot).is_none() => {
+            Some(slot)
+        }
+        _ => inventory.find_empty_slot(),
+    };
+
+    if inventory_slot.is_none() {
+        let error_msg = MessageType::Error(String::from("Inventory is full"));
+        state.ui.message_log.add_message(error_msg);
+        return;
+    }
+
+    let action_details = PickUpAction {
+        item: items_at[0],
+        inventory_slot: inventory_slot.unwrap(),
+    };
+
+    let msg = Message::Act(Action::PickUp(action_details));
+    network::send_message(outgoing_tx, msg);
+}
+
+// Take the first item from a corpse on the selected square, into the
+// selected inventory slot if it is empty and otherwise into the first
+// empty slot.
+pub fn loot_corpse(outgoing_tx: &Sender<Message>, state: &mut GameState) {
+    let acting_creature_id = state
+        .acting_creature
+        .expect("it should be the player's turn");
+    let acting_creature = state.layer.creatures.get(&acting_creature_id).unwrap();
+    let inventory = &acting_creature.inventory;
+
+    let selected_square = state.ui.viewport.get_selected_world_square();
+    let loot = state
+        .layer
+        .get_dead_creatures_at(selected_square)
+        .into_iter()
+        .find_map(|c_id| {
+            let corpse = state.layer.creatures.get(&c_id).unwrap();
+            (0..corpse.inventory.len())
+                .find_map(|slot| corpse.inventory.get_item(slot))
+                .map(|item_id| (c_id, item_id))
+        });
+
+    let (corpse, item) = match loot {
+        Some(loot) => loot,
+        None => {
+            let error_msg = MessageType::Error(String::from("There is nothing there to loot"));
+            state.ui.message_log.add_message(error_msg);
+            return;
+        }
+    };
+
+    let inventory_slot = match state.ui.inventory_info.get_selected_slot() {
+        Some(slot) if inventory.valid_slot(slot) && inventory.get_item(slot).is_none() => {
+            Some(slot)
+        }
+        _ => inventory.find_empty_slot(),
+    };
+
+    let inventory_slot = match inventory_slot {
+        Some(slot) => slot,
+        None => {
+            let error_msg = MessageType::Error(String::from("Inventory is full"));
+            state.ui.message_log.add_message(error_msg);
+            return;
+        }
+    };
+
+    let action_details = LootAction {
+        corpse,
+        item,
+        inventory_slot,
+    };
+
+    let msg = Message::Act(Action::Loot(action_details));
+    network::send_message(outgoing_tx, msg);
+}
+
+pub fn drop_item(outgoing_tx: &Sender<Message>, state: &mut GameState) {
+    let inventory_slot = match state.ui.inventory_info.get_selected_slot() {
+        Some(slot) => slot,
+        None => {
+            let error_msg = MessageType::Error(String::from("No inventory slot selected"));
+            state.ui.message_log.add_message(error_msg);
+            return;
+        }
+    };
+
+    let action_details = DropAction { inventory_slot };
+
+    let msg = Message::Act(Action::Drop(action_details));
+    network::send_message(outgoing_tx, msg);
+}
+
+// The first press marks the selected slot, the second one swaps the
+// marked slot with the slot selected at that point.
+pub fn swap_slots(outgoing_tx: &Sender<Message>, state: &mut GameState) {
+    let selected_slot = match state.ui.inventory_info.get_selected_slot() {
+        Some(slot) => slot,
+        None => {
+            let error_msg = MessageType::Error(String::from("No inventory slot selected"));
+            state.ui.message_log.add_message(error_msg);
+            return;
+        }
+    };
+
+    let marked_slot = match state.ui.inventory_info.take_marked_slot() {
+        Some(slot) => slot,
+        None => {
+            state.ui.inventory_info.mark_slot(selected_slot);
+            return;
+        }
+    };
+
+    if marked_slot == selected_slot {
+        return;
+    }
+
+    let action_details = SwapSlotsAction {
+        first_slot: marked_slot,
+        second_slot: selected_slot,
+    };
+
+    let msg = Message::Act(Action::SwapSlots(action_details));
+    network::send_message(outgoing_tx, msg);
+}
+
+// Equip the item in the selected slot, or unequip it if it is already equipped
+pub fn toggle_equipped(outgoing_tx: &Sender<Message>, state: &mut GameState) {
+    let acting_creature_id = state
+        .acting_creature
+        .expect("it should be the player's turn");
+    let acting_creature = state.layer.creatures.get(&acting_creature_id).unwrap();
+
+    let inventory_slot = state.ui.inventory_info.get_selected_slot();
+    let item_id = match inventory_slot {
+        Some(slot) => acting_creature.inventory.get_item(slot),
+        None => None,
+    };
+
+    if item_id.is_none() {
+        let error_msg = MessageType::Error(String::from("Inventory slot is empty"));
+        state.ui.message_log.add_message(error_msg);
+        return;
+    }
+
+    let action_details = EquipAction {
+        inventory_slot: inventory_slot.expect("slot should have been checked to exist"),
+    };
+
+    let cur_action = if acting_creature.equipment.is_equipped(item_id.unwrap()) {
+        Action::Unequip(action_details)
+    } else {
+        Action::Equip(action_details)
+    };
+
+    let msg = Message::Act(cur_action);
+    network::send_message(outgoing_tx, msg);
+}
+
+pub fn end_turn(outgoing_tx: &Sender<Message>) {
+    let msg = Message::Act(Action::EndTurn);
+    network::send_message(outgoing_tx, msg);
+}
+
+// Take the rest of the turn after the other creatures in the round
+pub fn delay(outgoing_tx: &Sender<Message>) {
+    let msg = Message::Act(Action::Delay);
+    network::send_message(outgoing_tx, msg);
+}
+
+// Answer the reaction prompt, if there is one that hasn't expired
+pub fn react(accept: bool, outgoing_tx: &Sender<Message>, state: &mut GameState) {
+    let deadline = match state.pending_reaction.take() {
+        Some(d) => d,
+        None => {
+            let error_msg = MessageType::Error(String::from("Nothing to react to"));
+            state.ui.message_log.add_message(error_msg);
+            return;
+        }
+    };
+
+    if Instant::now() > deadline {
+        let error_msg = MessageType::Error(String::from("Too late to react"));
+        state.ui.message_log.add_message(error_msg);
+        return;
+    }
+
+    let msg = Message::React(ReactMsg { accept });
+    network::send_message(outgoing_tx, msg);
+}
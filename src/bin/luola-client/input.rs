@@ -1,5 +1,7 @@
 use crate::actions;
+use crate::ui::UI;
 use crate::GameState;
+use luola::creature::character::CharacterOptions;
 use luola::info_message::MessageType;
 use luola::messages::Message;
 use std::io::{self, ErrorKind, Read};
@@ -19,10 +21,29 @@ pub enum InputEvent {
     MoveSelection(Direction),
     MoveCreature,
     UseItem,
+    PickUpItem,
+    LootCorpse,
+    DropItem,
+    SwapSlots,
+    ToggleEquipped,
+    EndTurn,
+    Delay,
+    Interact,
+    DisarmTrap,
+    Dig,
+    Stabilize,
+    React(bool),
     SelectInventorySlot(usize),
+    Confirm,
 }
 
 fn allowed_to_act(state: &mut GameState) -> bool {
+    if state.game_over {
+        let error_msg = MessageType::Error(String::from("Can't act: the game is over"));
+        state.ui.message_log.add_message(error_msg);
+        return false;
+    }
+
     let acting_creature = match state.acting_creature {
         Some(c) => c,
         None => return false,
@@ -37,11 +58,7 @@ fn allowed_to_act(state: &mut GameState) -> bool {
     true
 }
 
-pub fn handle_input(
-    input_rx: &Receiver<InputEvent>,
-    outgoing_tx: &Sender<Message>,
-    state: &mut GameState,
-) {
+fn receive_input_events(input_rx: &Receiver<InputEvent>) -> Vec<InputEvent> {
     let mut input_events: Vec<InputEvent> = Vec::new();
 
     // We could just iterate over try_iter() but it does not allow us to distinguish
@@ -58,7 +75,36 @@ pub fn handle_input(
         }
     }
 
-    for event in input_events {
+    input_events
+}
+
+// Returns the chosen character options once the player confirms them.
+pub fn handle_character_creation_input(
+    input_rx: &Receiver<InputEvent>,
+    ui: &mut UI,
+) -> Option<CharacterOptions> {
+    let creation = &mut ui.character_creation;
+
+    for event in receive_input_events(input_rx) {
+        match event {
+            InputEvent::MoveSelection(Direction::Up) => creation.move_selection(-1),
+            InputEvent::MoveSelection(Direction::Down) => creation.move_selection(1),
+            InputEvent::MoveSelection(_) => creation.switch_column(),
+            InputEvent::SelectInventorySlot(option) => creation.select_option(option),
+            InputEvent::Confirm => return Some(creation.get_options()),
+            _ => (),
+        }
+    }
+
+    None
+}
+
+pub fn handle_input(
+    input_rx: &Receiver<InputEvent>,
+    outgoing_tx: &Sender<Message>,
+    state: &mut GameState,
+) {
+    for event in receive_input_events(input_rx) {
         match event {
             InputEvent::MoveSelection(direction) => actions::move_selection(direction, state),
             InputEvent::SelectInventorySlot(slot) => {
@@ -74,6 +120,63 @@ pub fn handle_input(
                     actions::use_item(outgoing_tx, state);
                 }
             }
+            InputEvent::PickUpItem => {
+                if allowed_to_act(state) {
+                    actions::pick_up_item(outgoing_tx, state);
+                }
+            }
+            InputEvent::LootCorpse => {
+                if allowed_to_act(state) {
+                    actions::loot_corpse(outgoing_tx, state);
+                }
+            }
+            InputEvent::DropItem => {
+                if allowed_to_act(state) {
+                    actions::drop_item(outgoing_tx, state);
+                }
+            }
+            InputEvent::SwapSlots => {
+                if allowed_to_act(state) {
+                    actions::swap_slots(outgoing_tx, state);
+                }
+            }
+            InputEvent::ToggleEquipped => {
+                if allowed_to_act(state) {
+                    actions::toggle_equipped(outgoing_tx, state);
+                }
+            }
+            InputEvent::EndTurn => {
+                if allowed_to_act(state) {
+                    actions::end_turn(outgoing_tx);
+                }
+            }
+            InputEvent::Delay => {
+                if allowed_to_act(state) {
+                    actions::delay(outgoing_tx);
+                }
+            }
+            InputEvent::Interact => {
+                if allowed_to_act(state) {
+                    actions::interact(outgoing_tx, state);
+                }
+            }
+            InputEvent::DisarmTrap => {
+                if allowed_to_act(state) {
+                    actions::disarm_trap(outgoing_tx, state);
+                }
+            }
+            InputEvent::Dig => {
+                if allowed_to_act(state) {
+                    actions::dig(outgoing_tx, state);
+                }
+            }
+            InputEvent::Stabilize => {
+                if allowed_to_act(state) {
+                    actions::stabilize(outgoing_tx, state);
+                }
+            }
+            InputEvent::React(accept) => actions::react(accept, outgoing_tx, state),
+            InputEvent::Confirm => (),
         }
     }
 }
@@ -119,7 +222,21 @@ fn poll_input() -> Vec<InputEvent> {
             'a' => Some(InputEvent::MoveSelection(Direction::Left)),
             'd' => Some(InputEvent::MoveSelection(Direction::Right)),
             'q' => Some(InputEvent::UseItem),
+            'g' => Some(InputEvent::PickUpItem),
+            'l' => Some(InputEvent::LootCorpse),
+            'r' => Some(InputEvent::DropItem),
+            'x' => Some(InputEvent::SwapSlots),
+            'f' => Some(InputEvent::ToggleEquipped),
+            'e' => Some(InputEvent::EndTurn),
+            't' => Some(InputEvent::Delay),
+            'i' => Some(InputEvent::Interact),
+            'v' => Some(InputEvent::DisarmTrap),
+            'b' => Some(InputEvent::Dig),
+            'h' => Some(InputEvent::Stabilize),
+            'y' => Some(InputEvent::React(true)),
+            'n' => Some(InputEvent::React(false)),
             ' ' => Some(InputEvent::MoveCreature),
+            '\r' | '\n' => Some(InputEvent::Confirm),
             '1'..='9' => {
                 let slot = ((c as u32) - ('1' as u32)) as usize;
                 Some(InputEvent::SelectInventorySlot(slot))
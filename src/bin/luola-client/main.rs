@@ -1,9 +1,13 @@
+use crate::input::InputEvent;
 use crate::terminal::Terminal;
 use crate::ui::UI;
 use luola::constants;
+use luola::creature::character::CharacterOptions;
 use luola::messages::CreatureOwner;
 use luola::world::Layer;
 use std::collections::HashMap;
+use std::sync::mpsc::Receiver;
+use std::time::Instant;
 use std::{thread, time};
 
 mod actions;
@@ -17,7 +21,11 @@ pub struct GameState {
     ui: UI,
     creature_owners: HashMap<u128, CreatureOwner>,
     acting_creature: Option<u128>,
+    // Deadline for answering the reaction the server offered
+    pending_reaction: Option<Instant>,
     player_id: u128,
+    // The server closes the connection after the game ends
+    game_over: bool,
 }
 
 impl GameState {
@@ -38,6 +46,25 @@ impl GameState {
     }
 }
 
+fn create_character(
+    terminal: &mut Terminal,
+    ui: &mut UI,
+    input_rx: &Receiver<InputEvent>,
+) -> CharacterOptions {
+    loop {
+        if let Some(options) = input::handle_character_creation_input(input_rx, ui) {
+            return options;
+        }
+
+        let rendered_ui = ui.render_character_creation();
+        terminal.next_frame.paste(&rendered_ui, 0, 0);
+        terminal.render_next();
+
+        let delay = time::Duration::from_millis(16);
+        thread::sleep(delay);
+    }
+}
+
 fn main() {
     let server_address = String::from("127.0.0.1:26988");
     let (tx_stream, rx_stream) = network::open_stream(server_address);
@@ -45,14 +72,15 @@ fn main() {
     let incoming_rx = network::spawn_incoming_thread(rx_stream);
     let outgoing_tx = network::spawn_outgoing_thread(tx_stream);
 
-    let player_id = network::join_game(&outgoing_tx, &incoming_rx);
-
     let width: usize = 162;
     let height: usize = 48;
     let mut terminal = Terminal::init(width, height);
-    let ui = UI::new(width, height);
+    let mut ui = UI::new(width, height);
     let input_rx = input::spawn_polling_thread();
 
+    let character = create_character(&mut terminal, &mut ui, &input_rx);
+    let player_id = network::join_game(&outgoing_tx, &incoming_rx, character);
+
     // Use a dummy layer until we receive the actual one so that we
     // don't have to deal with Option<Layer> everywhere
     let layer = Layer::new(constants::WORLD_HEIGHT, constants::WORLD_WIDTH);
@@ -62,7 +90,9 @@ fn main() {
         ui,
         creature_owners: HashMap::new(),
         acting_creature: None,
+        pending_reaction: None,
         player_id,
+        game_over: false,
     };
 
     loop {
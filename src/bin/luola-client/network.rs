@@ -1,11 +1,13 @@
 use crate::GameState;
 use luola::constants;
+use luola::creature::character::CharacterOptions;
 use luola::info_message::MessageType;
 use luola::messages::*;
 use luola::world::Layer;
 use std::net::TcpStream;
 use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
 use std::thread;
+use std::time::{Duration, Instant};
 
 // Open two handles to the same underlying socket. This allows us to
 // read and write simultaneously in separate threads.
@@ -26,8 +28,14 @@ pub fn spawn_incoming_thread(mut rx_stream: TcpStream) -> Receiver<Message> {
 
     thread::spawn(move || loop {
         let rx_message = luola::net::receive(&mut rx_stream);
+        let game_over = matches!(rx_message, Message::GameOver(_));
 
         incoming_tx.send(rx_message).expect("failed to mpsc-send incoming message: mpsc receiver has disconnected, did the main thread panic?");
+
+        // Nothing more is coming after the game ends
+        if game_over {
+            break;
+        }
     });
 
     incoming_rx
@@ -61,7 +69,13 @@ fn receive_message(incoming_rx: &Receiver<Message>) -> Message {
 fn handle_rx_message(message: Message, _outgoing_tx: &Sender<Message>, state: &mut GameState) {
     match message {
         Message::GameState(game_state) => {
-            let layer = Layer::reconstruct(game_state.grid, game_state.creatures, game_state.items);
+            let layer = Layer::reconstruct(
+                game_state.grid,
+                game_state.creatures,
+                game_state.items,
+                game_state.item_positions,
+                game_state.traps,
+            );
             state.layer = layer;
             state.creature_owners = game_state.creature_owners;
 
@@ -84,6 +98,23 @@ fn handle_rx_message(message: Message, _outgoing_tx: &Sender<Message>, state: &m
             state.acting_creature = Some(msg.acting_creature);
             state.ui.viewport.center_at(creature.get_position());
         }
+        Message::ActionsLeft(msg) => {
+            state.ui.inventory_info.set_actions_left(msg);
+        }
+        Message::ReactionPrompt(msg) => {
+            let prompt = MessageType::Info(format!("{} [y/n]", msg.description));
+            state.ui.message_log.add_message(prompt);
+
+            state.pending_reaction = Some(Instant::now() + Duration::from_millis(msg.timeout_ms));
+        }
+        Message::GameOver(msg) => {
+            state
+                .ui
+                .message_log
+                .add_message(MessageType::Info(msg.message));
+            state.acting_creature = None;
+            state.game_over = true;
+        }
         Message::ActionOk => (),
         Message::ActionError => (),
         _ => panic!("received unexpected message type: {}", message),
@@ -95,29 +126,31 @@ pub fn handle_messaging(
     outgoing_tx: &Sender<Message>,
     state: &mut GameState,
 ) {
-    let mut received_messages: Vec<Message> = Vec::new();
-
+    // Messages are handled as they come, so that a game over is known
+    // by the time the connection closes
     loop {
         match incoming_rx.try_recv() {
-            Ok(msg) => received_messages.push(msg),
+            Ok(msg) => handle_rx_message(msg, outgoing_tx, state),
             Err(e) => match e {
                 TryRecvError::Empty => break,
+                TryRecvError::Disconnected if state.game_over => break,
                 TryRecvError::Disconnected => {
                     panic!("failed to mpsc-receive incoming message: mpsc sender has disconnected")
                 }
             },
         }
     }
-
-    for rx_message in received_messages {
-        handle_rx_message(rx_message, outgoing_tx, state);
-    }
 }
 
-pub fn join_game(outgoing_tx: &Sender<Message>, incoming_rx: &Receiver<Message>) -> u128 {
+pub fn join_game(
+    outgoing_tx: &Sender<Message>,
+    incoming_rx: &Receiver<Message>,
+    character: CharacterOptions,
+) -> u128 {
     let join_msg = Message::Join(JoinMsg {
         version: constants::get_version(),
         character_name: String::from("testcharacter"),
+        character,
     });
 
     send_message(outgoing_tx, join_msg);
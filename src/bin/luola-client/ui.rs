@@ -1,4 +1,5 @@
 use crate::terminal::canvas::Canvas;
+use crate::ui::character_creation::CharacterCreation;
 use crate::ui::creature_info::CreatureInfo;
 use crate::ui::inventory_info::InventoryInfo;
 use crate::ui::message_log::MessageLog;
@@ -6,6 +7,7 @@ use crate::ui::viewport::Viewport;
 use crate::GameState;
 
 mod borders;
+mod character_creation;
 mod color_scheme;
 mod creature_info;
 mod inventory_info;
@@ -20,6 +22,7 @@ pub struct UI {
     creature_info: CreatureInfo,
     pub inventory_info: InventoryInfo,
     pub message_log: MessageLog,
+    pub character_creation: CharacterCreation,
 
     displayed_creature: Option<u128>,
     default_displayed_creature: Option<u128>,
@@ -43,6 +46,7 @@ impl UI {
             creature_info: CreatureInfo::new(sidebar_width - 2, height / 2 - 2),
             inventory_info,
             message_log: MessageLog::new(viewport_width - 2, message_log_height - 2),
+            character_creation: CharacterCreation::new(width - 2, height - 2),
 
             displayed_creature: None,
             default_displayed_creature: None,
@@ -83,6 +87,12 @@ impl UI {
         canvas
     }
 
+    pub fn render_character_creation(&self) -> Canvas {
+        let character_creation = self.character_creation.render();
+
+        borders::add_rounded_borders(&character_creation, color_scheme::BORDER_STYLE)
+    }
+
     pub fn get_displayed_creature(&self) -> Option<u128> {
         if self.displayed_creature.is_some() {
             return self.displayed_creature;
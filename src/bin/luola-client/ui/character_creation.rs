@@ -0,0 +1,149 @@
+use crate::terminal::canvas::Canvas;
+use crate::ui::color_scheme;
+use luola::creature::character::{Ancestry, CharacterOptions, Class};
+
+#[derive(PartialEq)]
+enum Column {
+    Ancestry,
+    Class,
+}
+
+pub struct CharacterCreation {
+    width: usize,
+    height: usize,
+
+    ancestries: Vec<Ancestry>,
+    classes: Vec<Class>,
+
+    selected_ancestry: usize,
+    selected_class: usize,
+    focus: Column,
+}
+
+impl CharacterCreation {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            ancestries: Ancestry::all(),
+            classes: Class::all(),
+            selected_ancestry: 0,
+            selected_class: 0,
+            focus: Column::Ancestry,
+        }
+    }
+
+    pub fn move_selection(&mut self, delta: i32) {
+        let (selected, n_options) = match self.focus {
+            Column::Ancestry => (&mut self.selected_ancestry, self.ancestries.len()),
+            Column::Class => (&mut self.selected_class, self.classes.len()),
+        };
+
+        let n_options = n_options as i32;
+        *selected = ((*selected as i32 + delta).rem_euclid(n_options)) as usize;
+    }
+
+    pub fn select_option(&mut self, option: usize) {
+        match self.focus {
+            Column::Ancestry => {
+                if option < self.ancestries.len() {
+                    self.selected_ancestry = option;
+                }
+            }
+            Column::Class => {
+                if option < self.classes.len() {
+                    self.selected_class = option;
+                }
+            }
+        }
+    }
+
+    pub fn switch_column(&mut self) {
+        self.focus = match self.focus {
+            Column::Ancestry => Column::Class,
+            Column::Class => Column::Ancestry,
+        };
+    }
+
+    pub fn get_options(&self) -> CharacterOptions {
+        CharacterOptions {
+            ancestry: self.ancestries[self.selected_ancestry],
+            class: self.classes[self.selected_class],
+        }
+    }
+
+    pub fn render(&self) -> Canvas {
+        let mut canvas = Canvas::new(self.width, self.height);
+
+        canvas.write(
+            String::from("Create your character"),
+            color_scheme::TEXT_HIGHLIGHT_STYLE,
+        );
+        canvas.write_newline();
+        canvas.write_newline();
+
+        let ancestry_names: Vec<String> = self.ancestries.iter().map(|a| a.to_string()).collect();
+        self.write_options(
+            &mut canvas,
+            "Ancestry",
+            &ancestry_names,
+            self.selected_ancestry,
+            self.focus == Column::Ancestry,
+        );
+        canvas.write(
+            String::from(self.ancestries[self.selected_ancestry].description()),
+            color_scheme::TEXT_STYLE,
+        );
+        canvas.write_newline();
+        canvas.write_newline();
+
+        let class_names: Vec<String> = self.classes.iter().map(|c| c.to_string()).collect();
+        self.write_options(
+            &mut canvas,
+            "Class",
+            &class_names,
+            self.selected_class,
+            self.focus == Column::Class,
+        );
+        canvas.write(
+            String::from(self.classes[self.selected_class].description()),
+            color_scheme::TEXT_STYLE,
+        );
+        canvas.write_newline();
+        canvas.write_newline();
+
+        canvas.write(
+            String::from("[w/s] choose, [a/d] switch between ancestry and class, [enter] start"),
+            color_scheme::TEXT_STYLE,
+        );
+
+        canvas
+    }
+
+    fn write_options(
+        &self,
+        canvas: &mut Canvas,
+        title: &str,
+        options: &[String],
+        selected: usize,
+        focused: bool,
+    ) {
+        canvas.write(String::from(title), color_scheme::TEXT_HIGHLIGHT_STYLE);
+        canvas.write_newline();
+
+        for (i, option) in options.iter().enumerate() {
+            if i == selected {
+                let style = if focused {
+                    color_scheme::SELECTION_STYLE
+                } else {
+                    color_scheme::TEXT_HIGHLIGHT_STYLE
+                };
+
+                canvas.write(format!("{} {}", color_scheme::BULLET_POINT, option), style);
+            } else {
+                canvas.write(format!("  {}", option), color_scheme::TEXT_STYLE);
+            }
+            canvas.write_newline();
+        }
+    }
+}
@@ -31,6 +31,46 @@ pub const WORLD_WALL_STYLE: Style = Style {
     background_color: WORLD_WALL_COLOR,
 };
 
+pub const WORLD_DIFFICULT_TERRAIN_STYLE: Style = Style {
+    foreground_color: Color::Green,
+    background_color: WORLD_BACKGROUND_COLOR,
+};
+
+pub const WORLD_WATER_STYLE: Style = Style {
+    foreground_color: Color::BrightCyan,
+    background_color: Color::Blue,
+};
+
+pub const WORLD_CHASM_STYLE: Style = Style {
+    foreground_color: Color::BrightBlack,
+    background_color: WORLD_BACKGROUND_COLOR,
+};
+
+pub const WORLD_RUBBLE_STYLE: Style = Style {
+    foreground_color: Color::White,
+    background_color: WORLD_BACKGROUND_COLOR,
+};
+
+pub const WORLD_DOOR_STYLE: Style = Style {
+    foreground_color: Color::Yellow,
+    background_color: WORLD_BACKGROUND_COLOR,
+};
+
+pub const WORLD_LOCKED_DOOR_STYLE: Style = Style {
+    foreground_color: Color::Red,
+    background_color: WORLD_BACKGROUND_COLOR,
+};
+
+pub const WORLD_LEVER_STYLE: Style = Style {
+    foreground_color: Color::Black,
+    background_color: WORLD_WALL_COLOR,
+};
+
+pub const WORLD_STAIRS_STYLE: Style = Style {
+    foreground_color: Color::BrightWhite,
+    background_color: WORLD_BACKGROUND_COLOR,
+};
+
 pub const BORDER_STYLE: Style = Style {
     foreground_color: BORDER_COLOR,
     background_color: WIDGET_BACKGROUND_COLOR,
@@ -106,4 +146,14 @@ pub const ACTIVE_NONPLAYER_CREATURE_STYLE: Style = Style {
     background_color: WORLD_BACKGROUND_COLOR,
 };
 
+pub const ITEM_STYLE: Style = Style {
+    foreground_color: Color::Yellow,
+    background_color: WORLD_BACKGROUND_COLOR,
+};
+
+pub const TRAP_STYLE: Style = Style {
+    foreground_color: Color::BrightRed,
+    background_color: Color::Transparent,
+};
+
 pub const BULLET_POINT: &str = "\u{25ba}";
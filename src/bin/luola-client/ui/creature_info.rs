@@ -1,5 +1,7 @@
 use crate::terminal::canvas::Canvas;
 use crate::ui::color_scheme;
+use luola::constants;
+use luola::creature::DownedState;
 use luola::stat::Stat;
 use luola::world::Layer;
 
@@ -29,6 +31,44 @@ impl CreatureInfo {
             color_scheme::TEXT_STYLE,
         );
         canvas.write_newline();
+
+        // Only the items matter for corpses
+        if creature.is_dead() {
+            canvas.write(String::from("Corpse"), color_scheme::TEXT_STYLE);
+            canvas.write_newline();
+            canvas.write_newline();
+
+            canvas.write(String::from("Items"), color_scheme::TEXT_HIGHLIGHT_STYLE);
+            canvas.write_newline();
+            let items: Vec<u128> = (0..creature.inventory.len())
+                .filter_map(|slot| creature.inventory.get_item(slot))
+                .collect();
+            if items.is_empty() {
+                canvas.write(String::from("Nothing"), color_scheme::TEXT_STYLE);
+                canvas.write_newline();
+            }
+            for item_id in items {
+                let item = layer.items.get(&item_id).unwrap();
+                canvas.write(
+                    format!("{} {}", color_scheme::BULLET_POINT, item.name),
+                    color_scheme::TEXT_STYLE,
+                );
+                canvas.write_newline();
+            }
+
+            return canvas;
+        }
+
+        canvas.write(String::from("XP "), color_scheme::TEXT_HIGHLIGHT_STYLE);
+        canvas.write(
+            format!(
+                "{}/{}",
+                creature.stats.experience,
+                constants::EXPERIENCE_PER_LEVEL
+            ),
+            color_scheme::TEXT_STYLE,
+        );
+        canvas.write_newline();
         canvas.write_newline();
 
         let current_hp = creature.stats.current_hp as f64;
@@ -46,6 +86,18 @@ impl CreatureInfo {
             color_scheme::TEXT_STYLE,
         );
         canvas.write_newline();
+
+        let downed = match creature.downed {
+            Some(DownedState::Dying(value)) => {
+                Some(format!("Dying {}/{}", value, constants::DEATH_THRESHOLD))
+            }
+            Some(DownedState::Stable) => Some(String::from("Stable")),
+            _ => None,
+        };
+        if let Some(downed) = downed {
+            canvas.write(downed, color_scheme::TEXT_HIGHLIGHT_STYLE);
+            canvas.write_newline();
+        }
         canvas.write(String::from("Max HP "), color_scheme::TEXT_HIGHLIGHT_STYLE);
         write_value_stat(&mut canvas, &creature.stats.max_hp, creature.stats.level);
         canvas.write_newline();
@@ -119,6 +171,30 @@ impl CreatureInfo {
         );
         write_value_stat(&mut canvas, &creature.stats.will_dc, creature.stats.level);
         canvas.write_newline();
+
+        for (damage_type, stat) in &creature.stats.resistances {
+            canvas.write(
+                format!("{} Resist {} ", color_scheme::BULLET_POINT, damage_type),
+                color_scheme::TEXT_HIGHLIGHT_STYLE,
+            );
+            write_value_stat(&mut canvas, stat, creature.stats.level);
+            canvas.write_newline();
+        }
+        for (damage_type, stat) in &creature.stats.weaknesses {
+            canvas.write(
+                format!("{} Weak to {} ", color_scheme::BULLET_POINT, damage_type),
+                color_scheme::TEXT_HIGHLIGHT_STYLE,
+            );
+            write_value_stat(&mut canvas, stat, creature.stats.level);
+            canvas.write_newline();
+        }
+        for damage_type in &creature.stats.immunities {
+            canvas.write(
+                format!("{} Immune to {}", color_scheme::BULLET_POINT, damage_type),
+                color_scheme::TEXT_HIGHLIGHT_STYLE,
+            );
+            canvas.write_newline();
+        }
         canvas.write_newline();
 
         canvas.write(String::from("Other"), color_scheme::TEXT_HIGHLIGHT_STYLE);
@@ -2,6 +2,7 @@ use crate::terminal::canvas::Canvas;
 use crate::ui::color_scheme;
 use luola::creature::inventory::Inventory;
 use luola::item::Item;
+use luola::messages::ActionsLeftMsg;
 use luola::world::Layer;
 
 pub struct InventoryInfo {
@@ -9,6 +10,10 @@ pub struct InventoryInfo {
     height: usize,
 
     selected_slot: Option<usize>,
+    // Slot waiting to be swapped with the next selected slot
+    marked_slot: Option<usize>,
+    // Action budget of the creature whose turn it is
+    actions_left: Option<ActionsLeftMsg>,
 }
 
 impl InventoryInfo {
@@ -17,6 +22,8 @@ impl InventoryInfo {
             width,
             height,
             selected_slot: None,
+            marked_slot: None,
+            actions_left: None,
         }
     }
 
@@ -28,6 +35,18 @@ impl InventoryInfo {
         self.selected_slot
     }
 
+    pub fn mark_slot(&mut self, slot: usize) {
+        self.marked_slot = Some(slot);
+    }
+
+    pub fn take_marked_slot(&mut self) -> Option<usize> {
+        self.marked_slot.take()
+    }
+
+    pub fn set_actions_left(&mut self, actions_left: ActionsLeftMsg) {
+        self.actions_left = Some(actions_left);
+    }
+
     pub fn render(&self, creature_id: Option<u128>, layer: &Layer) -> Canvas {
         let mut canvas = Canvas::new(self.width, self.height);
 
@@ -39,6 +58,13 @@ impl InventoryInfo {
         let creature = layer.creatures.get(&creature_id).unwrap();
         let inventory = &creature.inventory;
 
+        if let Some(actions) = &self.actions_left {
+            if actions.acting_creature == creature_id {
+                self.write_actions_left(&mut canvas, actions);
+                canvas.write_newline();
+            }
+        }
+
         canvas.write(
             String::from("Inventory"),
             color_scheme::TEXT_HIGHLIGHT_STYLE,
@@ -47,7 +73,8 @@ impl InventoryInfo {
 
         for slot in 0..inventory.len() {
             let item = get_item_from_slot(slot, inventory, layer);
-            self.write_inventory_slot(&mut canvas, item, slot);
+            let equipped = item.is_some_and(|x| creature.equipment.is_equipped(x.get_id()));
+            self.write_inventory_slot(&mut canvas, item, slot, equipped);
             canvas.write_newline();
         }
         canvas.write_newline();
@@ -61,7 +88,13 @@ impl InventoryInfo {
         canvas
     }
 
-    fn write_inventory_slot(&self, canvas: &mut Canvas, item: Option<&Item>, slot: usize) {
+    fn write_inventory_slot(
+        &self,
+        canvas: &mut Canvas,
+        item: Option<&Item>,
+        slot: usize,
+        equipped: bool,
+    ) {
         let mut slot_style = color_scheme::TEXT_HIGHLIGHT_STYLE;
 
         if let Some(selected) = self.selected_slot {
@@ -74,11 +107,42 @@ impl InventoryInfo {
         canvas.write(format!("[{}]: ", slot + 1), slot_style);
 
         let slot_contents = match item {
-            Some(x) => x.name.clone(),
+            Some(x) => match x.charges {
+                Some(charges) => format!("{} x{}", x.name, charges),
+                None => x.name.clone(),
+            },
             None => String::from("(empty)"),
         };
 
         canvas.write(slot_contents, color_scheme::TEXT_STYLE);
+
+        if equipped {
+            canvas.write(
+                String::from(" (equipped)"),
+                color_scheme::TEXT_HIGHLIGHT_STYLE,
+            );
+        }
+
+        if self.marked_slot == Some(slot) {
+            canvas.write(String::from(" (swap)"), color_scheme::TEXT_HIGHLIGHT_STYLE);
+        }
+    }
+
+    fn write_actions_left(&self, canvas: &mut Canvas, actions: &ActionsLeftMsg) {
+        canvas.write(
+            String::from("Actions: "),
+            color_scheme::TEXT_HIGHLIGHT_STYLE,
+        );
+
+        // One filled diamond for every action left, hollow ones for used actions
+        let used = (actions.max_actions - actions.actions_left).max(0);
+        let left = actions.actions_left.max(0);
+        let symbols = format!(
+            "{}{}",
+            "\u{25c6}".repeat(left as usize),
+            "\u{25c7}".repeat(used as usize)
+        );
+        canvas.write(symbols, color_scheme::SELECTION_STYLE);
     }
 
     fn write_selected_item_details(&self, canvas: &mut Canvas, item: &Item) {
@@ -86,6 +150,30 @@ impl InventoryInfo {
         canvas.write_newline();
 
         canvas.write(item.description.clone(), color_scheme::TEXT_STYLE);
+
+        if item.action_cost != 1 {
+            canvas.write_newline();
+            canvas.write(
+                format!("Takes {} actions to use", item.action_cost),
+                color_scheme::TEXT_STYLE,
+            );
+        }
+
+        if let Some(charges) = item.charges {
+            canvas.write_newline();
+            canvas.write(
+                format!("Charges left: {}", charges),
+                color_scheme::TEXT_STYLE,
+            );
+        }
+
+        for affix in &item.affixes {
+            canvas.write_newline();
+            canvas.write(
+                format!("{} {}", color_scheme::BULLET_POINT, affix),
+                color_scheme::TEXT_STYLE,
+            );
+        }
     }
 }
 
@@ -13,6 +13,26 @@ pub struct MessageLog {
     messages: Vec<MessageType>,
 }
 
+// Such as "2 walls and 1 door come crashing down"
+fn describe_broken_tiles(walls: i32, doors: i32) -> String {
+    let count = |n: i32, noun: &str| match n {
+        1 => format!("1 {}", noun),
+        _ => format!("{} {}s", n, noun),
+    };
+
+    let mut parts: Vec<String> = Vec::new();
+    if walls > 0 {
+        parts.push(count(walls, "wall"));
+    }
+    if doors > 0 {
+        parts.push(count(doors, "door"));
+    }
+
+    let verb = if walls + doors == 1 { "comes" } else { "come" };
+
+    format!("{} {} crashing down", parts.join(" and "), verb)
+}
+
 impl MessageLog {
     pub fn new(width: usize, height: usize) -> Self {
         Self {
@@ -63,6 +83,7 @@ impl MessageLog {
             MessageType::Info(msg) => self.render_info_message(msg),
             MessageType::Error(msg) => self.render_error_message(msg),
             MessageType::Attack(msg) => self.render_attack_message(msg, layer),
+            MessageType::Experience(msg) => self.render_experience_message(msg, layer),
         }
     }
 
@@ -126,6 +147,11 @@ impl MessageLog {
                 color_scheme::TEXT_STYLE,
             );
 
+            // Circumstances change the DC, so they are listed separately
+            for circumstance in &result.check.circumstances {
+                canvas.write(format!("{}, ", circumstance), color_scheme::TEXT_STYLE);
+            }
+
             match result.check.outcome {
                 Outcome::CriticalSuccess => {
                     canvas.write(
@@ -147,14 +173,79 @@ impl MessageLog {
                 }
             }
 
-            if result.damage > 0 {
+            if let Some(roll) = &result.roll {
+                canvas.write(format!(", {}", roll), color_scheme::TEXT_STYLE);
+
+                if let Outcome::CriticalSuccess = result.check.outcome {
+                    canvas.write(String::from(" x2"), color_scheme::CRITICAL_SUCCESS_STYLE);
+                }
+            }
+
+            let damage = &result.damage;
+            if damage.base > 0 {
                 canvas.write(String::from(", "), color_scheme::TEXT_STYLE);
                 canvas.write(
-                    format!("{}", result.damage),
+                    format!("{}", damage.total),
                     color_scheme::TEXT_HIGHLIGHT_STYLE,
                 );
-                canvas.write(String::from(" damage"), color_scheme::TEXT_STYLE);
+                canvas.write(
+                    format!(" {} damage", damage.damage_type),
+                    color_scheme::TEXT_STYLE,
+                );
+
+                if let Some(adjustment) = damage.describe_adjustment() {
+                    canvas.write(format!(" ({})", adjustment), color_scheme::TEXT_STYLE);
+                }
+            }
+        }
+
+        if message.broken_walls > 0 || message.broken_doors > 0 {
+            if !message.results.is_empty() {
+                canvas.write_newline();
             }
+            canvas.write(
+                format!("{} ", color_scheme::BULLET_POINT),
+                color_scheme::TEXT_HIGHLIGHT_STYLE,
+            );
+            canvas.write(
+                describe_broken_tiles(message.broken_walls, message.broken_doors),
+                color_scheme::TEXT_STYLE,
+            );
+        }
+
+        canvas
+    }
+
+    fn render_experience_message(&self, message: &ExperienceMessage, layer: &Layer) -> Canvas {
+        let mut canvas = Canvas::new_transparent(self.width, self.height);
+
+        let creature = layer.creatures.get(&message.creature).unwrap();
+        let defeated = layer.creatures.get(&message.defeated).unwrap();
+
+        canvas.write(
+            format!("{} ", creature.name),
+            color_scheme::TEXT_HIGHLIGHT_STYLE,
+        );
+        canvas.write(String::from("gains "), color_scheme::TEXT_STYLE);
+        canvas.write(
+            format!("{} XP", message.experience),
+            color_scheme::TEXT_HIGHLIGHT_STYLE,
+        );
+        canvas.write(
+            format!(" for defeating {}", defeated.name),
+            color_scheme::TEXT_STYLE,
+        );
+
+        if let Some(level) = message.new_level {
+            canvas.write_newline();
+            canvas.write(
+                format!("{} {} ", color_scheme::BULLET_POINT, creature.name),
+                color_scheme::TEXT_HIGHLIGHT_STYLE,
+            );
+            canvas.write(
+                format!("reaches level {}!", level),
+                color_scheme::CRITICAL_SUCCESS_STYLE,
+            );
         }
 
         canvas
@@ -1,8 +1,10 @@
 use crate::terminal::canvas::Canvas;
+use crate::terminal::styled_char::Style;
 use crate::ui::color_scheme;
 use crate::GameState;
 use luola::creature::Creature;
-use luola::grid::{Grid, GridSquare, Tile};
+use luola::grid::{DoorState, Grid, GridSquare, LeverState, StairsDirection, Tile};
+use luola::trap::Trap;
 use std::collections::HashMap;
 
 const TILE_WIDTH: usize = 2 * TILE_HEIGHT;
@@ -44,6 +46,12 @@ impl Viewport {
     pub fn render(&self, state: &GameState) -> Canvas {
         let mut grid = self.render_grid(&state.layer.grid);
 
+        let items = self.render_items(state.layer.item_positions.positions());
+        grid.paste(&items, 0, 0);
+
+        let traps = self.render_traps(&state.layer.traps);
+        grid.paste(&traps, 0, 0);
+
         let creatures = self.render_creatures(&state.layer.creatures, state);
         grid.paste(&creatures, 0, 0);
 
@@ -157,9 +165,105 @@ impl Viewport {
             Tile::Empty => {
                 Canvas::new_style(TILE_WIDTH, TILE_HEIGHT, color_scheme::WORLD_EMPTY_STYLE)
             }
+            Tile::DifficultTerrain => {
+                self.render_patterned_tile(",\"", color_scheme::WORLD_DIFFICULT_TERRAIN_STYLE)
+            }
+            Tile::Water => self.render_patterned_tile("~ ", color_scheme::WORLD_WATER_STYLE),
+            Tile::Chasm => self.render_patterned_tile("\u{2591}", color_scheme::WORLD_CHASM_STYLE),
+            Tile::Rubble => self.render_patterned_tile(".:", color_scheme::WORLD_RUBBLE_STYLE),
+            Tile::Door(DoorState::Open) => {
+                self.render_patterned_tile("\u{2591}", color_scheme::WORLD_DOOR_STYLE)
+            }
+            Tile::Door(DoorState::Closed) => {
+                self.render_patterned_tile("\u{2593}", color_scheme::WORLD_DOOR_STYLE)
+            }
+            Tile::Door(DoorState::Locked) => {
+                self.render_patterned_tile("\u{2593}", color_scheme::WORLD_LOCKED_DOOR_STYLE)
+            }
+            Tile::Lever(LeverState::Up) => {
+                self.render_patterned_tile(" /", color_scheme::WORLD_LEVER_STYLE)
+            }
+            Tile::Lever(LeverState::Pulled) => {
+                self.render_patterned_tile("\\ ", color_scheme::WORLD_LEVER_STYLE)
+            }
+            Tile::Stairs(StairsDirection::Up) => {
+                self.render_patterned_tile("<", color_scheme::WORLD_STAIRS_STYLE)
+            }
+            Tile::Stairs(StairsDirection::Down) => {
+                self.render_patterned_tile(">", color_scheme::WORLD_STAIRS_STYLE)
+            }
         }
     }
 
+    // Fill the tile by repeating the pattern, shifted by one on every other row
+    fn render_patterned_tile(&self, pattern: &str, style: Style) -> Canvas {
+        let mut canvas = Canvas::new_style(TILE_WIDTH, TILE_HEIGHT, style);
+        let pattern: Vec<char> = pattern.chars().collect();
+
+        for row in 0..TILE_HEIGHT {
+            let line: String = (0..TILE_WIDTH)
+                .map(|col| pattern[(row + col) % pattern.len()])
+                .collect();
+
+            canvas.set_cursor_position(row, 0);
+            canvas.write(line, style);
+        }
+
+        canvas
+    }
+
+    fn render_traps(&self, traps: &HashMap<u128, Trap>) -> Canvas {
+        let mut canvas = Canvas::new_transparent(self.width_chars(), self.height_chars());
+
+        for trap in traps.values() {
+            let viewport_square = match self.world_to_viewport(trap.position) {
+                Some(s) => s,
+                None => continue,
+            };
+
+            let mut rendered_trap = Canvas::new_transparent(TILE_WIDTH, TILE_HEIGHT);
+
+            // Opposite corner from items, so that both can be seen
+            rendered_trap.set_cursor_position(0, 0);
+            rendered_trap.write(String::from("^"), color_scheme::TRAP_STYLE);
+
+            canvas.paste(
+                &rendered_trap,
+                (viewport_square.y as usize) * TILE_HEIGHT,
+                (viewport_square.x as usize) * TILE_WIDTH,
+            );
+        }
+
+        canvas
+    }
+
+    fn render_items(&self, item_positions: &HashMap<u128, GridSquare>) -> Canvas {
+        let mut canvas = Canvas::new_transparent(self.width_chars(), self.height_chars());
+
+        for world_square in item_positions.values() {
+            let viewport_square = match self.world_to_viewport(*world_square) {
+                Some(s) => s,
+                None => continue,
+            };
+
+            let mut rendered_item = Canvas::new_transparent(TILE_WIDTH, TILE_HEIGHT);
+
+            // Items are drawn in the corner so that creatures standing
+            // on top of them do not hide them completely
+            rendered_item.set_cursor_position(TILE_HEIGHT - 1, TILE_WIDTH - 1);
+            // "black diamond"
+            rendered_item.write(String::from("\u{25c6}"), color_scheme::ITEM_STYLE);
+
+            canvas.paste(
+                &rendered_item,
+                (viewport_square.y as usize) * TILE_HEIGHT,
+                (viewport_square.x as usize) * TILE_WIDTH,
+            );
+        }
+
+        canvas
+    }
+
     fn render_creatures(&self, creatures: &HashMap<u128, Creature>, state: &GameState) -> Canvas {
         let mut canvas = Canvas::new_transparent(self.width_chars(), self.height_chars());
 
@@ -213,6 +317,9 @@ impl Viewport {
             // "black large circle"
             // If the space after is removed, the circle is drawn only partially
             String::from("\u{2b24} ")
+        } else if creature.is_downed() {
+            // "heavy circle", an unconscious creature
+            String::from("\u{2b58} ")
         } else {
             String::from("\u{2716}")
         };
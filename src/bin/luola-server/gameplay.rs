@@ -1,13 +1,19 @@
 use crate::messaging;
 use luola::ai;
 use luola::constants;
-use luola::creature::action::{self, Action};
+use luola::creature::action::{self, Action, TakenAction, Turn};
+use luola::creature::downed;
+use luola::creature::experience;
 use luola::creature::perception::{Awareness, Perception};
+use luola::creature::reaction::{self, Reaction, ReactionTracker};
+use luola::creature::DownedState;
+use luola::info_message::MessageType;
 use luola::initiative::Initiative;
+use luola::item::effect;
 use luola::player::Player;
 use luola::world::{Layer, World};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::thread;
 
 #[derive(Deserialize, PartialEq, Serialize)]
@@ -15,11 +21,78 @@ enum GameplayMode {
     Start,
     Exploration,
     Combat,
+    GameOver,
+}
+
+fn award_kill_experience(
+    killer_id: u128,
+    living_before: Vec<u128>,
+    layer: &mut Layer,
+    players: &mut HashMap<u128, Player>,
+) {
+    for victim_id in living_before {
+        // Creatures can die by their own doing, for example to a trap
+        if victim_id == killer_id || layer.creatures.get(&victim_id).unwrap().is_alive() {
+            continue;
+        }
+
+        if let Some(xp_message) = experience::award_kill_experience(killer_id, victim_id, layer) {
+            messaging::send_info_message(xp_message, players);
+        }
+    }
+}
+
+fn update_downed(layer: &mut Layer, players: &mut HashMap<u128, Player>) {
+    for info_message in downed::update_downed(layer) {
+        messaging::send_info_message(info_message, players);
+    }
+}
+
+// Offers the reactions to the reacting creatures one by one. Players are
+// prompted, AI creatures decide immediately.
+fn resolve_reactions(
+    reactions: Vec<Reaction>,
+    tracker: &mut ReactionTracker,
+    layer: &mut Layer,
+    players: &mut HashMap<u128, Player>,
+) {
+    for r in reactions {
+        // An earlier reaction may have made this one impossible
+        if !reaction::is_valid(&r, tracker, layer) {
+            continue;
+        }
+
+        let reactor_id = r.get_reactor();
+        let c_ai = layer.creature_ai.get(&reactor_id).unwrap();
+        let accept = match c_ai.get_controlling_player_id() {
+            Some(player_id) => {
+                let player = players
+                    .get_mut(&player_id)
+                    .expect("creature should be controlled by an existing player");
+                messaging::get_player_reaction(player, r, layer)
+            }
+            None => ai::react(c_ai, &r, layer),
+        };
+
+        if !accept {
+            continue;
+        }
+
+        println!("creature {} reacts", reactor_id);
+
+        let living_before_reaction = layer.get_living_creatures();
+        let info_message = reaction::execute(&r, tracker, layer);
+        messaging::send_info_message(info_message, players);
+
+        award_kill_experience(reactor_id, living_before_reaction, layer, players);
+    }
 }
 
 // Returns true if a non-player character is alerted.
 fn take_creature_turn(
     creature_id: u128,
+    turn: &mut Turn,
+    reactions: &mut ReactionTracker,
     layer: &mut Layer,
     players: &mut HashMap<u128, Player>,
     gameplay_mode: GameplayMode,
@@ -27,18 +100,31 @@ fn take_creature_turn(
 ) -> Option<bool> {
     let creature = layer.creatures.get(&creature_id).unwrap();
     if !creature.is_alive() {
+        // Dying creatures spend their turn fighting for their life
+        if let Some(DownedState::Dying(_)) = creature.downed {
+            let info_message = downed::recovery_check(creature_id, layer);
+            messaging::send_info_message(info_message, players);
+            messaging::send_game_state(layer, players);
+        }
+
         return None;
     }
 
     messaging::send_turn_start_message(creature_id, players);
 
-    let mut prev_actions: Vec<Action> = Vec::new();
     let creature_max_actions = creature.stats.n_actions.get_value(creature.stats.level);
 
-    while (prev_actions.len() as i32) < creature_max_actions {
+    messaging::send_actions_left_message(
+        creature_id,
+        creature_max_actions,
+        creature_max_actions,
+        players,
+    );
+
+    loop {
         let creature = layer.creatures.get(&creature_id).unwrap();
 
-        if !creature.is_alive() {
+        if !creature.is_alive() || turn.is_over(creature) {
             break;
         }
 
@@ -48,13 +134,13 @@ fn take_creature_turn(
                 let mut player = players
                     .get_mut(&player_id)
                     .expect("creature should be controlled by an existing player");
-                messaging::get_player_action(&mut player, &prev_actions, creature, layer)
+                messaging::get_player_action(&mut player, turn, creature, layer)
             }
             None => {
                 thread::sleep(constants::AI_ACTION_DELAY);
 
                 let ai_action = ai::act(c_ai, layer);
-                if action::is_valid(&ai_action, &prev_actions, creature, layer).is_err() {
+                if action::is_valid(&ai_action, turn, creature, layer).is_err() {
                     panic!("AI should not take an invalid action");
                 }
 
@@ -64,16 +150,62 @@ fn take_creature_turn(
 
         println!("creature {} acts", creature.get_id());
 
-        if let Some(info_message) = action::execute(&cur_action, creature_id, layer) {
-            messaging::send_info_message(info_message, players);
+        // The cost has to be known before executing, because using up
+        // an item removes it from the inventory
+        let cost = action::get_cost(&cur_action, creature, layer);
+        let react = gameplay_mode == GameplayMode::Combat;
+
+        // Moving away from enemies provokes attacks before the move
+        // happens, which may stop the creature for good.
+        if let Action::Move(m) = &cur_action {
+            if react {
+                let move_reactions =
+                    reaction::get_move_reactions(creature_id, m.destination, reactions, layer);
+                resolve_reactions(move_reactions, reactions, layer, players);
+            }
         }
 
-        prev_actions.push(cur_action);
+        let living_before_action = layer.get_living_creatures();
+
+        let mut is_attack = false;
+        if layer.creatures.get(&creature_id).unwrap().is_alive() {
+            if let Some(info_message) = action::execute(&cur_action, turn, creature_id, layer) {
+                let hit_reactions = match &info_message {
+                    MessageType::Attack(a) if react => {
+                        reaction::get_hit_reactions(a, reactions, layer)
+                    }
+                    _ => Vec::new(),
+                };
+
+                is_attack = matches!(info_message, MessageType::Attack(_));
+                messaging::send_info_message(info_message, players);
+
+                resolve_reactions(hit_reactions, reactions, layer, players);
+            }
+        }
+
+        award_kill_experience(creature_id, living_before_action, layer, players);
+        update_downed(layer, players);
+
+        turn.prev_actions.push(TakenAction {
+            action: cur_action,
+            cost,
+            is_attack,
+        });
+
+        let creature = layer.creatures.get(&creature_id).unwrap();
+        messaging::send_actions_left_message(
+            creature_id,
+            turn.get_actions_left(creature),
+            creature_max_actions,
+            players,
+        );
 
         Perception::update_all_observations(
             &mut layer.creature_ai,
             &layer.grid,
             &layer.creatures,
+            &layer.creature_positions,
             current_round,
         );
 
@@ -121,6 +253,12 @@ fn take_creature_turn(
     return Some(false);
 }
 
+// Creatures take their turns in initiative order. A creature that delays
+// is moved to the back of the queue, marked as having delayed.
+fn get_turn_queue(order: Vec<(i32, u128)>) -> VecDeque<(u128, bool)> {
+    order.into_iter().map(|(_, id)| (id, false)).collect()
+}
+
 fn run_exploration_round(
     layer: &mut Layer,
     players: &mut HashMap<u128, Player>,
@@ -130,7 +268,10 @@ fn run_exploration_round(
     let aware = init.get_aware(&layer.creature_ai);
     let wandering = init.get_wandering(&layer.creature_ai);
 
-    for (_, creature_id) in aware {
+    let mut turn_queue = get_turn_queue(aware);
+    let mut reactions = ReactionTracker::new();
+
+    while let Some((creature_id, delayed)) = turn_queue.pop_front() {
         assert!(
             layer
                 .creature_ai
@@ -143,17 +284,28 @@ fn run_exploration_round(
         // If a creature is alerted because of a player action,
         // immediately transition to combat. Otherwise players would always get the
         // first attack by rushing in and attacking.
+        let mut turn = Turn::new(delayed);
         let someone_alerted = take_creature_turn(
             creature_id,
+            &mut turn,
+            &mut reactions,
             layer,
             players,
             GameplayMode::Exploration,
             current_round,
         );
 
+        if downed::is_party_defeated(layer) {
+            return GameplayMode::GameOver;
+        }
+
         if someone_alerted.is_some_and(|x| x) {
             return GameplayMode::Combat;
         }
+
+        if turn.is_delaying() {
+            turn_queue.push_back((creature_id, true));
+        }
     }
 
     // If a creature is alerted while it wanders, do not switch to combat immediately.
@@ -165,12 +317,18 @@ fn run_exploration_round(
     for (_, creature_id) in wandering {
         let someone_alerted = take_creature_turn(
             creature_id,
+            &mut Turn::new(false),
+            &mut reactions,
             layer,
             players,
             GameplayMode::Exploration,
             current_round,
         );
 
+        if downed::is_party_defeated(layer) {
+            return GameplayMode::GameOver;
+        }
+
         if someone_alerted.is_some_and(|x| x) {
             wandering_creature_alerted = true;
         }
@@ -192,29 +350,50 @@ fn run_combat_round(
     let aware = init.get_aware(&layer.creature_ai);
     let wandering = init.get_wandering(&layer.creature_ai);
 
-    for (_, creature_id) in aware {
+    let mut turn_queue = get_turn_queue(aware);
+    let mut reactions = ReactionTracker::new();
+
+    while let Some((creature_id, delayed)) = turn_queue.pop_front() {
+        let mut turn = Turn::new(delayed);
         let someone_alerted = take_creature_turn(
             creature_id,
+            &mut turn,
+            &mut reactions,
             layer,
             players,
             GameplayMode::Combat,
             current_round,
         );
 
+        if downed::is_party_defeated(layer) {
+            return GameplayMode::GameOver;
+        }
+
         if someone_alerted.is_some_and(|x| !x) {
             return GameplayMode::Exploration;
         }
+
+        if turn.is_delaying() {
+            turn_queue.push_back((creature_id, true));
+        }
     }
 
     // todo: fast-forward these somehow
     for (_, creature_id) in wandering {
         let someone_alerted = take_creature_turn(
             creature_id,
+            &mut Turn::new(false),
+            &mut reactions,
             layer,
             players,
             GameplayMode::Combat,
             current_round,
         );
+
+        if downed::is_party_defeated(layer) {
+            return GameplayMode::GameOver;
+        }
+
         if someone_alerted.is_some_and(|x| !x) {
             return GameplayMode::Exploration;
         }
@@ -234,6 +413,13 @@ pub fn run_game(mut world: World, mut players: HashMap<u128, Player>) {
     messaging::send_game_state(&world.layers[current_layer], &mut players);
 
     loop {
+        if next_mode == GameplayMode::GameOver {
+            println!("all player characters are down, game over");
+            let message = String::from("The whole party has fallen. Game over.");
+            messaging::send_game_over_message(message, &mut players);
+            return;
+        }
+
         if next_mode != current_mode {
             init = Initiative::roll_initiative(&world.layers[current_layer]);
 
@@ -245,6 +431,7 @@ pub fn run_game(mut world: World, mut players: HashMap<u128, Player>) {
                     println!("switch to combat mode");
                 }
                 GameplayMode::Start => panic!("can't transition into start mode"),
+                GameplayMode::GameOver => unreachable!(),
             }
 
             current_mode = next_mode;
@@ -270,6 +457,18 @@ pub fn run_game(mut world: World, mut players: HashMap<u128, Player>) {
                 );
             }
             GameplayMode::Start => panic!("must switch away from start mode"),
+            GameplayMode::GameOver => unreachable!(),
+        }
+
+        let layer = &mut world.layers[current_layer];
+        for info_message in effect::tick_ongoing_effects(layer) {
+            messaging::send_info_message(info_message, &mut players);
+        }
+        update_downed(layer, &mut players);
+        messaging::send_game_state(layer, &mut players);
+
+        if downed::is_party_defeated(layer) {
+            next_mode = GameplayMode::GameOver;
         }
 
         current_round += 1;
@@ -1,38 +1,48 @@
-use luola::ai::Behavior;
+use luola::creature::bestiary::Bestiary;
+use luola::item::catalog::ItemCatalog;
 use luola::player::Player;
+use luola::vault::Vaults;
 use luola::world::World;
+use luola::worldgen;
+use rand::prelude::*;
+use rand_chacha::ChaCha20Rng;
 use std::collections::HashMap;
 
 mod gameplay;
 mod messaging;
-mod worldgen;
 
 fn main() {
     let n_players: usize = 1;
     let worldgen_seed: u64 = 1;
 
+    let catalog = match ItemCatalog::load_default() {
+        Ok(c) => c,
+        Err(e) => panic!("failed to load item catalog: {}", e),
+    };
+    println!("loaded {} item definitions", catalog.get_all().len());
+
+    let bestiary = match Bestiary::load_default(&catalog) {
+        Ok(b) => b,
+        Err(e) => panic!("failed to load bestiary: {}", e),
+    };
+    println!("loaded {} creature definitions", bestiary.get_all().len());
+
+    let vaults = match Vaults::load_default(&bestiary, &catalog) {
+        Ok(v) => v,
+        Err(e) => panic!("failed to load vaults: {}", e),
+    };
+    println!("loaded {} vaults", vaults.get_all().len());
+
     println!("generating world with seed {}", worldgen_seed);
-    let mut world: World = worldgen::generate_world(worldgen_seed);
+    let mut world: World =
+        worldgen::generate_world(worldgen_seed, n_players, &bestiary, &catalog, &vaults);
     println!("world generated with {} layers", world.layers.len());
 
     let players: HashMap<u128, Player> = messaging::wait_for_join(n_players);
     println!("{} players connected, ready to start", players.len());
 
-    // debug: make one creature player-controlled
-    let mut player_id: u128 = 0;
-    let mut creature_id: u128 = 0;
-    'outer: for (pid, _) in &players {
-        for (cid, _) in &world.layers[0].creatures {
-            player_id = *pid;
-            creature_id = *cid;
-            break 'outer;
-        }
-    }
-    world.layers[0]
-        .creature_ai
-        .get_mut(&creature_id)
-        .unwrap()
-        .set_override_behavior(Behavior::PlayerControlled(player_id));
+    let mut spawn_rng = ChaCha20Rng::seed_from_u64(worldgen_seed);
+    worldgen::spawn_player_characters(&mut world.layers[0], &players, &catalog, &mut spawn_rng);
 
     // debug: add stat modifiers
     for (_, c) in &mut world.layers[0].creatures {
@@ -1,5 +1,6 @@
 use luola::constants;
-use luola::creature::action::{self, Action};
+use luola::creature::action::{self, Action, Turn};
+use luola::creature::reaction::Reaction;
 use luola::creature::Creature;
 use luola::info_message::MessageType;
 use luola::messages::*;
@@ -7,6 +8,7 @@ use luola::player::Player;
 use luola::world::Layer;
 use std::collections::HashMap;
 use std::net::{TcpListener, TcpStream};
+use std::time::Instant;
 
 fn handle_join(mut socket: TcpStream) -> Option<Player> {
     let msg = luola::net::receive(&mut socket);
@@ -31,7 +33,12 @@ fn handle_join(mut socket: TcpStream) -> Option<Player> {
                 return None;
             }
 
-            let mut player = Player::new(socket);
+            println!(
+                "{} joins as a {} {}",
+                join_msg.character_name, join_msg.character.ancestry, join_msg.character.class
+            );
+
+            let mut player = Player::new(socket, join_msg.character_name, join_msg.character);
 
             let response = JoinOkMsg {
                 player_id: player.get_id(),
@@ -103,6 +110,23 @@ pub fn send_turn_start_message(acting_creature: u128, players: &mut HashMap<u128
     }
 }
 
+pub fn send_actions_left_message(
+    acting_creature: u128,
+    actions_left: i32,
+    max_actions: i32,
+    players: &mut HashMap<u128, Player>,
+) {
+    let message = ActionsLeftMsg {
+        acting_creature,
+        actions_left,
+        max_actions,
+    };
+    let message = Message::ActionsLeft(message);
+    for player in players.values_mut() {
+        luola::net::send(&mut player.socket, &message);
+    }
+}
+
 pub fn send_info_message(info_message: MessageType, players: &mut HashMap<u128, Player>) {
     let message = Message::Info(info_message);
     for (_, player) in players {
@@ -110,9 +134,16 @@ pub fn send_info_message(info_message: MessageType, players: &mut HashMap<u128,
     }
 }
 
+pub fn send_game_over_message(message: String, players: &mut HashMap<u128, Player>) {
+    let message = Message::GameOver(GameOverMsg { message });
+    for player in players.values_mut() {
+        luola::net::send(&mut player.socket, &message);
+    }
+}
+
 pub fn get_player_action(
     player: &mut Player,
-    prev_actions: &Vec<Action>,
+    turn: &Turn,
     creature: &Creature,
     layer: &Layer,
 ) -> Action {
@@ -121,7 +152,7 @@ pub fn get_player_action(
 
         match msg {
             Message::Act(player_action) => {
-                match action::is_valid(&player_action, prev_actions, creature, layer) {
+                match action::is_valid(&player_action, turn, creature, layer) {
                     Ok(()) => {
                         let response = Message::ActionOk;
                         luola::net::send(&mut player.socket, &response);
@@ -140,6 +171,10 @@ pub fn get_player_action(
                     }
                 }
             }
+            // The answer to a reaction prompt that already timed out
+            Message::React(_) => {
+                println!("player {} reacted too late", player.get_id());
+            }
             other => {
                 println!(
                     "received unexpected message type: expected Act, got {}",
@@ -156,3 +191,41 @@ pub fn get_player_action(
         };
     }
 }
+
+// Returns true if the player takes the reaction. No answer before the
+// timeout counts as declining.
+pub fn get_player_reaction(player: &mut Player, reaction: Reaction, layer: &Layer) -> bool {
+    let prompt = ReactionPromptMsg {
+        reaction,
+        description: reaction.describe(layer),
+        timeout_ms: constants::REACTION_TIMEOUT.as_millis() as u64,
+    };
+    luola::net::send(&mut player.socket, &Message::ReactionPrompt(prompt));
+
+    let deadline = Instant::now() + constants::REACTION_TIMEOUT;
+
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            println!("player {} did not react in time", player.get_id());
+            return false;
+        }
+
+        match luola::net::receive_timeout(&mut player.socket, remaining) {
+            Some(Message::React(msg)) => return msg.accept,
+            Some(other) => {
+                println!(
+                    "received unexpected message type: expected React, got {}",
+                    other
+                );
+
+                let response = Message::ActionError;
+                luola::net::send(&mut player.socket, &response);
+            }
+            None => {
+                println!("player {} did not react in time", player.get_id());
+                return false;
+            }
+        }
+    }
+}
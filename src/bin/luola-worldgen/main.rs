@@ -0,0 +1,324 @@
+use luola::creature::bestiary::Bestiary;
+use luola::grid::gridalgos;
+use luola::grid::{DoorState, GridSquare, StairsDirection, Tile};
+use luola::item::catalog::ItemCatalog;
+use luola::vault::Vaults;
+use luola::world::Layer;
+use luola::worldgen;
+use std::collections::{BTreeMap, HashSet};
+use std::io::IsTerminal;
+use std::process;
+
+// Generates worlds without a server and prints them for inspection.
+// With --check, only the problems are printed, and the exit status tells
+// if any of the seeds had one, so that large ranges of seeds can be swept.
+const USAGE: &str = "usage: luola-worldgen [--players N] [--check] SEED [LAST_SEED]";
+
+struct Options {
+    n_players: usize,
+    check: bool,
+    first_seed: u64,
+    last_seed: u64,
+}
+
+fn parse_args(args: &[String]) -> Result<Options, String> {
+    let mut n_players: usize = 1;
+    let mut check = false;
+    let mut seeds: Vec<u64> = Vec::new();
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--check" => check = true,
+            "--players" => {
+                let value = args.next().ok_or("--players needs a value")?;
+                n_players = match value.parse() {
+                    Ok(n) if n > 0 => n,
+                    _ => return Err(format!("invalid number of players \"{}\"", value)),
+                };
+            }
+            _ => match arg.parse() {
+                Ok(seed) => seeds.push(seed),
+                Err(_) => return Err(format!("invalid seed \"{}\"", arg)),
+            },
+        }
+    }
+
+    let (first_seed, last_seed) = match seeds[..] {
+        [seed] => (seed, seed),
+        [first, last] if first <= last => (first, last),
+        [_, _] => return Err(String::from("the last seed is before the first one")),
+        _ => return Err(String::from("expected one seed or a range of seeds")),
+    };
+
+    Ok(Options {
+        n_players,
+        check,
+        first_seed,
+        last_seed,
+    })
+}
+
+// Doors count as open, since every door can be opened or broken
+fn is_open(layer: &Layer, square: GridSquare) -> bool {
+    match layer.grid.get_tile(square) {
+        Some(tile) => tile.is_passable() || matches!(tile, Tile::Door(_)),
+        None => false,
+    }
+}
+
+// Sizes of the groups of open squares that can reach each other, largest
+// first
+fn find_regions(layer: &Layer) -> Vec<usize> {
+    let mut visited: HashSet<GridSquare> = HashSet::new();
+    let mut sizes: Vec<usize> = Vec::new();
+
+    for y in 0..layer.grid.height() {
+        for x in 0..layer.grid.width() {
+            let start = GridSquare { y, x };
+            if !is_open(layer, start) || !visited.insert(start) {
+                continue;
+            }
+
+            let mut size = 0;
+            let mut stack = vec![start];
+
+            while let Some(square) = stack.pop() {
+                size += 1;
+
+                for nb in gridalgos::get_neighbors(square) {
+                    if is_open(layer, nb) && visited.insert(nb) {
+                        stack.push(nb);
+                    }
+                }
+            }
+
+            sizes.push(size);
+        }
+    }
+
+    sizes.sort_unstable_by(|a, b| b.cmp(a));
+    sizes
+}
+
+fn find_problems(layer: &Layer) -> Vec<String> {
+    let mut problems: Vec<String> = Vec::new();
+
+    let regions = find_regions(layer);
+    if regions.len() > 1 {
+        problems.push(format!(
+            "{} disconnected regions, the smallest has {} squares",
+            regions.len(),
+            regions.last().unwrap()
+        ));
+    }
+
+    let mut creatures: Vec<_> = layer.creatures.values().collect();
+    creatures.sort_by_key(|c| c.get_position());
+
+    for creature in creatures {
+        let position = creature.get_position();
+        if !layer.grid.passable_square(position) {
+            problems.push(format!(
+                "{} spawned inside a wall at {}",
+                creature.name, position
+            ));
+        }
+    }
+
+    // The vault comes with its own contents, and traps are never among them
+    if let Some((top_left, bottom_right)) = layer.vault_area {
+        let mut traps: Vec<GridSquare> = layer.traps.values().map(|t| t.position).collect();
+        traps.sort();
+
+        for position in traps {
+            if (top_left.y..=bottom_right.y).contains(&position.y)
+                && (top_left.x..=bottom_right.x).contains(&position.x)
+            {
+                problems.push(format!("trap inside the vault at {}", position));
+            }
+        }
+    }
+
+    problems
+}
+
+// (character, SGR parameters of its color)
+fn get_tile_symbol(tile: Tile) -> (char, &'static str) {
+    let color = match tile {
+        Tile::Empty => "90",
+        Tile::Wall | Tile::Rubble => "37",
+        Tile::DifficultTerrain => "32",
+        Tile::Water => "96;44",
+        Tile::Chasm => "",
+        Tile::Door(DoorState::Locked) => "31",
+        Tile::Door(_) | Tile::Lever(_) => "33",
+        Tile::Stairs(StairsDirection::Up | StairsDirection::Down) => "97",
+    };
+
+    (tile.to_char(), color)
+}
+
+// Creatures are shown by the first letter of their name, items as * and
+// traps as ^
+fn render_layer(layer: &Layer, color: bool) -> String {
+    let mut symbols: BTreeMap<GridSquare, (char, &str)> = BTreeMap::new();
+
+    for trap in layer.traps.values() {
+        symbols.insert(trap.position, ('^', "95"));
+    }
+    for square in layer.item_positions.positions().values() {
+        symbols.insert(*square, ('*', "93"));
+    }
+    for creature in layer.creatures.values() {
+        let letter = creature.name.chars().next().unwrap_or('?');
+        symbols.insert(creature.get_position(), (letter, "91"));
+    }
+
+    let mut rendered = String::new();
+
+    for y in 0..layer.grid.height() {
+        for x in 0..layer.grid.width() {
+            let square = GridSquare { y, x };
+            let (symbol, sgr) = match symbols.get(&square) {
+                Some(s) => *s,
+                None => get_tile_symbol(layer.grid.get_tile(square).unwrap()),
+            };
+
+            if color && !sgr.is_empty() {
+                rendered.push_str(&format!("\x1b[{}m{}\x1b[0m", sgr, symbol));
+            } else {
+                rendered.push(symbol);
+            }
+        }
+        rendered.push('\n');
+    }
+
+    rendered
+}
+
+fn describe_layer(layer: &Layer) -> String {
+    let height = layer.grid.height();
+    let width = layer.grid.width();
+
+    let mut free = 0;
+    for y in 0..height {
+        for x in 0..width {
+            if layer.grid.free_square(GridSquare { y, x }) {
+                free += 1;
+            }
+        }
+    }
+    let free_ratio = 100.0 * free as f64 / (height * width) as f64;
+
+    let regions = find_regions(layer);
+    let connectivity = match regions.len() {
+        0 => String::from("no open squares"),
+        1 => String::from("connected"),
+        n => format!("{} regions, largest {} squares", n, regions[0]),
+    };
+
+    let mut levels: BTreeMap<i32, usize> = BTreeMap::new();
+    for creature in layer.creatures.values() {
+        *levels.entry(creature.stats.level).or_insert(0) += 1;
+    }
+    let levels: Vec<String> = levels
+        .iter()
+        .map(|(level, count)| format!("{}x level {}", count, level))
+        .collect();
+
+    let on_floor = layer.item_positions.positions().len();
+    let carried = layer.items.len() - on_floor;
+    let with_affixes = layer
+        .items
+        .values()
+        .filter(|i| !i.affixes.is_empty())
+        .count();
+
+    format!(
+        "{}x{}, {:.1}% free squares, {}\n\
+         creatures: {} ({})\n\
+         items: {} on the floor, {} carried, {} with affixes\n\
+         traps: {}",
+        height,
+        width,
+        free_ratio,
+        connectivity,
+        layer.creatures.len(),
+        levels.join(", "),
+        on_floor,
+        carried,
+        with_affixes,
+        layer.traps.len()
+    )
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let options = match parse_args(&args) {
+        Ok(o) => o,
+        Err(e) => {
+            eprintln!("{}\n{}", e, USAGE);
+            process::exit(2);
+        }
+    };
+
+    let catalog = match ItemCatalog::load_default() {
+        Ok(c) => c,
+        Err(e) => panic!("failed to load item catalog: {}", e),
+    };
+
+    let bestiary = match Bestiary::load_default(&catalog) {
+        Ok(b) => b,
+        Err(e) => panic!("failed to load bestiary: {}", e),
+    };
+
+    let vaults = match Vaults::load_default(&bestiary, &catalog) {
+        Ok(v) => v,
+        Err(e) => panic!("failed to load vaults: {}", e),
+    };
+
+    let color = std::io::stdout().is_terminal();
+    let mut failed_seeds = 0;
+
+    for seed in options.first_seed..=options.last_seed {
+        let world = worldgen::generate_world(seed, options.n_players, &bestiary, &catalog, &vaults);
+        let mut failed = false;
+
+        if !options.check {
+            println!("seed {}", seed);
+        }
+
+        for (i, layer) in world.layers.iter().enumerate() {
+            let problems = find_problems(layer);
+            failed |= !problems.is_empty();
+
+            if options.check {
+                for problem in problems {
+                    println!("seed {}, layer {}: {}", seed, i, problem);
+                }
+                continue;
+            }
+
+            println!("\nlayer {}", i);
+            print!("{}", render_layer(layer, color));
+            println!("{}", describe_layer(layer));
+            for problem in problems {
+                println!("problem: {}", problem);
+            }
+        }
+
+        if failed {
+            failed_seeds += 1;
+        }
+    }
+
+    if options.check {
+        let n_seeds = options.last_seed - options.first_seed + 1;
+        println!("checked {} seeds, {} with problems", n_seeds, failed_seeds);
+    }
+
+    if failed_seeds > 0 {
+        process::exit(1);
+    }
+}
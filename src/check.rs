@@ -1,6 +1,7 @@
-use crate::creature::statistics::Statistics;
+use crate::creature::statistics::{CreatureStat, Statistics};
 use rand::prelude::*;
 use serde::{Deserialize, Serialize};
+use std::fmt;
 
 #[derive(Deserialize, Serialize)]
 pub enum Outcome {
@@ -10,12 +11,54 @@ pub enum Outcome {
     CriticalFailure,
 }
 
+// Situations that temporarily change the defender's DCs for a single
+// check
+#[derive(Clone, Copy, Deserialize, PartialEq, Serialize)]
+pub enum Circumstance {
+    Flanked,
+    LesserCover,
+    StandardCover,
+}
+
+impl Circumstance {
+    pub fn get_dc_modifier(&self) -> i32 {
+        match self {
+            Circumstance::Flanked => -2,
+            Circumstance::LesserCover => 1,
+            Circumstance::StandardCover => 2,
+        }
+    }
+
+    // Key of the temporary stat modifier
+    pub fn get_key(&self) -> u128 {
+        match self {
+            Circumstance::Flanked => 0x464c4b,
+            Circumstance::LesserCover => 0x4c4356,
+            Circumstance::StandardCover => 0x534356,
+        }
+    }
+}
+
+impl fmt::Display for Circumstance {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Circumstance::Flanked => "flanked",
+            Circumstance::LesserCover => "lesser cover",
+            Circumstance::StandardCover => "cover",
+        };
+
+        write!(f, "{} {:+}", name, self.get_dc_modifier())
+    }
+}
+
 #[derive(Deserialize, Serialize)]
 pub struct Check {
     pub roll: i32,
     pub modifier: i32,
+    // Includes the circumstances
     pub dc: i32,
     pub outcome: Outcome,
+    pub circumstances: Vec<Circumstance>,
 }
 
 impl Check {
@@ -25,6 +68,7 @@ impl Check {
             modifier,
             dc,
             outcome,
+            circumstances: Vec::new(),
         }
     }
 }
@@ -50,6 +94,70 @@ pub fn melee_attack_roll(att_stats: &Statistics, def_stats: &Statistics) -> Chec
     Check::new(roll, attack_bonus, ac, outcome)
 }
 
+pub fn ranged_attack_roll(att_stats: &Statistics, def_stats: &Statistics) -> Check {
+    let roll = d20();
+
+    let attack_bonus = att_stats.ranged_attack.get_value(att_stats.level);
+    let ac = def_stats.armor_class.get_value(def_stats.level);
+    let outcome = get_outcome(roll + attack_bonus, ac);
+
+    Check::new(roll, attack_bonus, ac, outcome)
+}
+
+pub fn magic_attack_roll(att_stats: &Statistics, def_stats: &Statistics) -> Check {
+    let roll = d20();
+
+    let attack_bonus = att_stats.magic_attack.get_value(att_stats.level);
+    let ac = def_stats.armor_class.get_value(def_stats.level);
+    let outcome = get_outcome(roll + attack_bonus, ac);
+
+    Check::new(roll, attack_bonus, ac, outcome)
+}
+
+// Area attacks that have to be dodged are rolled against reflex instead of AC
+pub fn ranged_reflex_roll(att_stats: &Statistics, def_stats: &Statistics) -> Check {
+    let roll = d20();
+
+    let attack_bonus = att_stats.ranged_attack.get_value(att_stats.level);
+    let dc = def_stats.reflex_dc.get_value(def_stats.level);
+    let outcome = get_outcome(roll + attack_bonus, dc);
+
+    Check::new(roll, attack_bonus, dc, outcome)
+}
+
+pub fn magic_reflex_roll(att_stats: &Statistics, def_stats: &Statistics) -> Check {
+    let roll = d20();
+
+    let attack_bonus = att_stats.magic_attack.get_value(att_stats.level);
+    let dc = def_stats.reflex_dc.get_value(def_stats.level);
+    let outcome = get_outcome(roll + attack_bonus, dc);
+
+    Check::new(roll, attack_bonus, dc, outcome)
+}
+
+// Hazards such as traps roll against one of the creature's saving throw DCs
+pub fn save_roll(bonus: i32, def_stats: &Statistics, save: CreatureStat) -> Check {
+    let roll = d20();
+
+    let dc = def_stats.get_stat(save).get_value(def_stats.level);
+    let outcome = get_outcome(roll + bonus, dc);
+
+    Check::new(roll, bonus, dc, outcome)
+}
+
+// A creature's own check against a fixed DC, such as spotting a trap
+pub fn skill_check(modifier: i32, dc: i32) -> Check {
+    let roll = d20();
+    let outcome = get_outcome(roll + modifier, dc);
+
+    Check::new(roll, modifier, dc, outcome)
+}
+
+// Short form of a check for info messages, such as 12+3 vs DC 15
+pub fn describe(check: &Check) -> String {
+    format!("{}{:+} vs DC {}", check.roll, check.modifier, check.dc)
+}
+
 pub fn d20() -> i32 {
     let mut rng = thread_rng();
     rng.gen_range(1..=20)
@@ -6,6 +6,9 @@ pub fn get_version() -> String {
     String::from(VERSION.unwrap_or("unknown-version"))
 }
 
+// Game data such as creature definitions is loaded from this directory.
+pub const DATA_DIRECTORY: &str = "data";
+
 pub const WORLD_HEIGHT: i32 = 50;
 pub const WORLD_WIDTH: i32 = 50;
 pub const WORLD_LAYERS: i32 = 10;
@@ -14,5 +17,29 @@ pub const WORLD_LAYERS: i32 = 10;
 // possible to have creatures that are weaker than new player characters.
 pub const MIN_LEVEL: i32 = -1;
 
+// Player characters stop gaining levels here.
+pub const MAX_LEVEL: i32 = 20;
+
+// A character levels up every time it has collected this much experience.
+pub const EXPERIENCE_PER_LEVEL: i32 = 1000;
+
+// Attack penalty for the second attack during a turn. Any further attacks
+// get twice the penalty.
+pub const MULTIPLE_ATTACK_PENALTY: i32 = 5;
+// Key of the temporary modifier that applies the penalty ("MAP")
+pub const MULTIPLE_ATTACK_PENALTY_KEY: u128 = 0x4d4150;
+
+// A downed player character dies when its dying value reaches this.
+pub const DEATH_THRESHOLD: i32 = 4;
+
+// Damage dealt to walls by digging without a melee weapon
+pub const UNARMED_DIG_DAMAGE: i32 = 2;
+
+// Pulling a lever opens every locked door this close to it.
+pub const LEVER_RADIUS: i32 = 10;
+
 pub const AI_ACTION_DELAY: Duration = time::Duration::from_millis(1000);
 pub const TURN_END_DELAY: Duration = time::Duration::from_millis(2000);
+
+// Time players have to answer a reaction prompt
+pub const REACTION_TIMEOUT: Duration = time::Duration::from_millis(5000);
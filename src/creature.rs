@@ -1,20 +1,42 @@
+use crate::constants;
+use crate::creature::equipment::{Equipment, EquipmentParams, StatModifier};
 use crate::creature::inventory::Inventory;
-use crate::creature::statistics::Statistics;
+use crate::creature::statistics::{CreatureStat, Statistics};
 use crate::grid::GridSquare;
 use rand::prelude::*;
 use serde::{Deserialize, Serialize};
 
 pub mod action;
-pub mod creature_types;
+pub mod bestiary;
+pub mod character;
+pub mod downed;
+pub mod encounter;
+pub mod equipment;
+pub mod experience;
 pub mod inventory;
 pub mod perception;
+pub mod positioning;
+pub mod reaction;
 pub mod statistics;
 
+// Player characters that drop to 0 HP are downed instead of dying right
+// away. Other creatures die as soon as they run out of HP.
+#[derive(Clone, Copy, Deserialize, PartialEq, Serialize)]
+pub enum DownedState {
+    Dying(i32),
+    // Unconscious, but no longer getting closer to death
+    Stable,
+    Dead,
+}
+
 #[derive(Clone, Deserialize, Serialize)]
 pub struct Creature {
     pub name: String,
     pub stats: Statistics,
     pub inventory: Inventory,
+    pub equipment: Equipment,
+    // Only set while the creature is at 0 HP
+    pub downed: Option<DownedState>,
 
     id: u128,
     position: GridSquare,
@@ -35,6 +57,8 @@ impl Creature {
             position: position,
             stats: stats,
             inventory: Inventory::new(inventory_slots),
+            equipment: Equipment::new(),
+            downed: None,
         }
     }
 
@@ -46,7 +70,8 @@ impl Creature {
         self.position
     }
 
-    pub fn set_position(&mut self, pos: &GridSquare) {
+    // Layer::move_creature also updates the position index of the layer
+    pub(crate) fn set_position(&mut self, pos: &GridSquare) {
         self.position.y = pos.y;
         self.position.x = pos.x;
     }
@@ -61,9 +86,156 @@ impl Creature {
         } else if self.stats.current_hp > max_hp {
             self.stats.current_hp = max_hp;
         }
+
+        // Healing brings downed creatures back on their feet
+        if self.stats.current_hp > 0 {
+            self.downed = None;
+        }
+    }
+
+    // Returns the number of levels gained. Experience stops accumulating
+    // at the maximum level.
+    pub fn gain_experience(&mut self, amount: i32) -> i32 {
+        let mut levels_gained = 0;
+
+        self.stats.experience += amount;
+
+        while self.stats.experience >= constants::EXPERIENCE_PER_LEVEL
+            && self.stats.level < constants::MAX_LEVEL
+        {
+            self.stats.experience -= constants::EXPERIENCE_PER_LEVEL;
+            self.level_up();
+            levels_gained += 1;
+        }
+
+        if self.stats.level >= constants::MAX_LEVEL {
+            self.stats.experience = 0;
+        }
+
+        levels_gained
+    }
+
+    fn level_up(&mut self) {
+        let old_max_hp = self.stats.max_hp.get_value(self.stats.level);
+        self.stats.level += 1;
+        let new_max_hp = self.stats.max_hp.get_value(self.stats.level);
+
+        // Keep the amount of damage taken the same so that leveling up
+        // does not fully heal the creature.
+        if self.is_alive() {
+            self.change_hp(new_max_hp - old_max_hp);
+        }
+    }
+
+    // The item's id is used as the key of its stat modifiers, so that they
+    // can be removed when the item is unequipped.
+    pub fn equip_item(&mut self, item_id: u128, params: &EquipmentParams) {
+        self.equipment.equip(item_id, params.slot);
+
+        for modifier in &params.modifiers {
+            match *modifier {
+                StatModifier::Additive(stat, value) => self
+                    .stats
+                    .get_stat_mut(stat)
+                    .apply_additive_modifier(item_id, value),
+                StatModifier::Multiplicative(stat, value) => self
+                    .stats
+                    .get_stat_mut(stat)
+                    .apply_multiplicative_modifier(item_id, value),
+            }
+        }
+
+        // Current HP can't exceed max HP if the item reduced it
+        self.change_hp(0);
+    }
+
+    // Does nothing if the item is not equipped.
+    pub fn unequip_item(&mut self, item_id: u128) {
+        if !self.equipment.unequip(item_id) {
+            return;
+        }
+
+        for stat in CreatureStat::all() {
+            self.stats.get_stat_mut(stat).remove_modifier(item_id);
+        }
+
+        // Current HP can't exceed the max HP that is left
+        self.change_hp(0);
     }
 
     pub fn is_alive(&self) -> bool {
         self.stats.current_hp > 0
     }
+
+    // Out of HP, but can still be saved
+    pub fn is_downed(&self) -> bool {
+        matches!(
+            self.downed,
+            Some(DownedState::Dying(_)) | Some(DownedState::Stable)
+        )
+    }
+
+    pub fn is_dead(&self) -> bool {
+        !self.is_alive() && !self.is_downed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create() -> Creature {
+        let position = GridSquare { y: 0, x: 0 };
+        Creature::new(
+            String::from("creature"),
+            position,
+            Statistics::get_default(),
+        )
+    }
+
+    #[test]
+    fn test_gain_experience() {
+        let mut creature = create();
+
+        assert_eq!(
+            creature.gain_experience(constants::EXPERIENCE_PER_LEVEL - 1),
+            0
+        );
+        assert_eq!(creature.stats.level, 1);
+
+        // The leftover carries over to the next level
+        assert_eq!(
+            creature.gain_experience(2 * constants::EXPERIENCE_PER_LEVEL),
+            2
+        );
+        assert_eq!(creature.stats.level, 3);
+        assert_eq!(
+            creature.stats.experience,
+            constants::EXPERIENCE_PER_LEVEL - 1
+        );
+
+        let amount = constants::MAX_LEVEL * constants::EXPERIENCE_PER_LEVEL;
+        assert_eq!(creature.gain_experience(amount), constants::MAX_LEVEL - 3);
+        assert_eq!(creature.stats.level, constants::MAX_LEVEL);
+        assert_eq!(creature.stats.experience, 0);
+
+        assert_eq!(creature.gain_experience(amount), 0);
+        assert_eq!(creature.stats.experience, 0);
+    }
+
+    #[test]
+    fn test_level_up_keeps_damage() {
+        let mut creature = create();
+        creature.change_hp(-3);
+
+        creature.gain_experience(constants::EXPERIENCE_PER_LEVEL);
+        let max_hp = creature.stats.max_hp.get_value(creature.stats.level);
+        assert_eq!(creature.stats.current_hp, max_hp - 3);
+
+        // The dead stay dead
+        let hp = creature.stats.current_hp;
+        creature.change_hp(-hp);
+        creature.gain_experience(constants::EXPERIENCE_PER_LEVEL);
+        assert_eq!(creature.stats.current_hp, 0);
+    }
 }
@@ -1,15 +1,36 @@
-use crate::creature::Creature;
+use crate::constants;
+use crate::creature::downed;
+use crate::creature::statistics::CreatureStat;
+use crate::creature::{Creature, DownedState};
 use crate::grid::gridalgos;
-use crate::grid::GridSquare;
+use crate::grid::{DoorState, GridSquare, LeverState, Tile};
 use crate::info_message::MessageType;
+use crate::item::targeting::{self, TargetKind, TargetingError};
+use crate::item::ItemKind;
+use crate::trap;
 use crate::world::Layer;
 use serde::{Deserialize, Serialize};
+use std::cmp;
 
 #[derive(Deserialize, Serialize)]
 pub enum Action {
     Idle,
+    // Pass all remaining actions
+    EndTurn,
+    // Take the turn later in the current round instead
+    Delay,
     Move(MoveAction),
     UseItem(UseItemAction),
+    PickUp(PickUpAction),
+    Loot(LootAction),
+    Drop(DropAction),
+    SwapSlots(SwapSlotsAction),
+    Equip(EquipAction),
+    Unequip(EquipAction),
+    Interact(InteractAction),
+    Disarm(DisarmAction),
+    Dig(DigAction),
+    Stabilize(StabilizeAction),
 }
 
 #[derive(Deserialize, Serialize)]
@@ -23,9 +44,176 @@ pub struct UseItemAction {
     pub target: GridSquare,
 }
 
+// Pick up an item from the square the creature is standing on
+#[derive(Deserialize, Serialize)]
+pub struct PickUpAction {
+    pub item: u128,
+    pub inventory_slot: usize,
+}
+
+// Take an item from a corpse on or next to the creature's square
+#[derive(Deserialize, Serialize)]
+pub struct LootAction {
+    pub corpse: u128,
+    pub item: u128,
+    pub inventory_slot: usize,
+}
+
+// Drop an item on the square the creature is standing on
+#[derive(Deserialize, Serialize)]
+pub struct DropAction {
+    pub inventory_slot: usize,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct SwapSlotsAction {
+    pub first_slot: usize,
+    pub second_slot: usize,
+}
+
+// Equip or unequip the item in an inventory slot
+#[derive(Deserialize, Serialize)]
+pub struct EquipAction {
+    pub inventory_slot: usize,
+}
+
+// Open or close a door, pull a lever and so on
+#[derive(Deserialize, Serialize)]
+pub struct InteractAction {
+    pub target: GridSquare,
+}
+
+// Try to disable a trap that has been found
+#[derive(Deserialize, Serialize)]
+pub struct DisarmAction {
+    pub target: GridSquare,
+}
+
+// Hack at a wall or door next to the creature until it breaks
+#[derive(Deserialize, Serialize)]
+pub struct DigAction {
+    pub target: GridSquare,
+}
+
+// Tend to a dying creature next to the actor
+#[derive(Deserialize, Serialize)]
+pub struct StabilizeAction {
+    pub target: GridSquare,
+}
+
+// An action that has been taken during the current turn
+#[derive(Deserialize, Serialize)]
+pub struct TakenAction {
+    pub action: Action,
+    pub cost: i32,
+    pub is_attack: bool,
+}
+
+// Stats that the multiple attack penalty applies to
+const ATTACK_STATS: [CreatureStat; 3] = [
+    CreatureStat::MeleeAttack,
+    CreatureStat::RangedAttack,
+    CreatureStat::MagicAttack,
+];
+
+// Number of actions the action uses from the creature's per-turn budget.
+// The action should be valid, otherwise the result is meaningless.
+pub fn get_cost(action: &Action, actor: &Creature, layer: &Layer) -> i32 {
+    match action {
+        Action::UseItem(u) => {
+            let item = actor
+                .inventory
+                .get_item(u.inventory_slot)
+                .and_then(|id| layer.items.get(&id));
+
+            match item {
+                Some(item) => item.action_cost,
+                None => 1,
+            }
+        }
+        // Letting go of an item is a free action
+        Action::Drop(_) => 0,
+        Action::EndTurn | Action::Delay => 0,
+        Action::Disarm(_) | Action::Stabilize(_) => 2,
+        _ => 1,
+    }
+}
+
+// A turn that is in progress
+pub struct Turn {
+    pub prev_actions: Vec<TakenAction>,
+    // Creatures can delay their turn only once per round
+    pub delayed: bool,
+}
+
+impl Turn {
+    pub fn new(delayed: bool) -> Self {
+        Self {
+            prev_actions: Vec::new(),
+            delayed,
+        }
+    }
+
+    pub fn get_actions_left(&self, actor: &Creature) -> i32 {
+        let max_actions = actor.stats.n_actions.get_value(actor.stats.level);
+        let used_actions: i32 = self.prev_actions.iter().map(|a| a.cost).sum();
+
+        max_actions - used_actions
+    }
+
+    // Every attack after the first one during a turn is harder to land.
+    pub fn get_multiple_attack_penalty(&self) -> i32 {
+        let prev_attacks = self.prev_actions.iter().filter(|a| a.is_attack).count() as i32;
+
+        -constants::MULTIPLE_ATTACK_PENALTY * cmp::min(prev_attacks, 2)
+    }
+
+    // Returns true if the creature chose to act later in the round.
+    pub fn is_delaying(&self) -> bool {
+        self.prev_actions
+            .last()
+            .is_some_and(|a| matches!(a.action, Action::Delay))
+    }
+
+    // Returns true if the creature can't take any more actions this turn.
+    pub fn is_over(&self, actor: &Creature) -> bool {
+        if self.get_actions_left(actor) <= 0 {
+            return true;
+        }
+
+        match self.prev_actions.last() {
+            Some(a) => matches!(a.action, Action::EndTurn | Action::Delay),
+            None => false,
+        }
+    }
+}
+
+fn check_slot_exists(actor: &Creature, slot: usize) -> Result<(), MessageType> {
+    if !actor.inventory.valid_slot(slot) {
+        return Err(MessageType::Error(format!(
+            "Inventory slot {} does not exist",
+            slot
+        )));
+    }
+
+    Ok(())
+}
+
+fn check_slot_has_item(actor: &Creature, slot: usize) -> Result<u128, MessageType> {
+    check_slot_exists(actor, slot)?;
+
+    match actor.inventory.get_item(slot) {
+        Some(item_id) => Ok(item_id),
+        None => Err(MessageType::Error(format!(
+            "Inventory slot {} is empty",
+            slot
+        ))),
+    }
+}
+
 pub fn is_valid(
     action: &Action,
-    _prev_actions: &Vec<Action>,
+    turn: &Turn,
     actor: &Creature,
     layer: &Layer,
 ) -> Result<(), MessageType> {
@@ -33,8 +221,37 @@ pub fn is_valid(
         return Err(MessageType::Error(String::from("Creature is dead")));
     }
 
+    if let Action::Delay = action {
+        if turn.delayed {
+            return Err(MessageType::Error(String::from(
+                "Can only delay once per round",
+            )));
+        }
+
+        if !turn.prev_actions.is_empty() {
+            return Err(MessageType::Error(String::from("Can't delay after acting")));
+        }
+    }
+
+    validate_action(action, actor, layer)?;
+
+    let cost = get_cost(action, actor, layer);
+    let actions_left = turn.get_actions_left(actor);
+    if cost > actions_left {
+        return Err(MessageType::Error(format!(
+            "Action costs {} actions, but only {} are left",
+            cost, actions_left
+        )));
+    }
+
+    Ok(())
+}
+
+// Checks the rules of the action itself, regardless of the earlier
+// actions taken during the turn.
+fn validate_action(action: &Action, actor: &Creature, layer: &Layer) -> Result<(), MessageType> {
     match action {
-        Action::Idle => Ok(()),
+        Action::Idle | Action::EndTurn | Action::Delay => Ok(()),
         Action::Move(m) => {
             let source = actor.get_position();
             let movement_speed = actor.stats.movement_speed.get_value(actor.stats.level);
@@ -45,9 +262,9 @@ pub fn is_valid(
                 )));
             }
 
-            if !layer.grid.free_square(m.destination) {
+            if !layer.grid.passable_square(m.destination) {
                 return Err(MessageType::Error(String::from(
-                    "Move destination square is inside a wall",
+                    "Move destination square can't be entered",
                 )));
             }
 
@@ -57,14 +274,8 @@ pub fn is_valid(
                 )));
             }
 
-            #[rustfmt::skip]
-            let all_paths = gridalgos::find_all_shortest_paths(
-                &vec![source],
-                movement_speed,
-                &layer,
-            );
-
-            let shortest_path = gridalgos::get_shortest_path(&all_paths, m.destination);
+            let shortest_path =
+                gridalgos::find_shortest_path(source, m.destination, movement_speed, layer);
 
             if shortest_path.is_none() {
                 return Err(MessageType::Error(String::from(
@@ -77,7 +288,6 @@ pub fn is_valid(
         Action::UseItem(u) => {
             // todo: check that the targets exist
             // todo: check that the targets are of the right type for the item
-            // todo: check that the targets fulfill the targeting constraints of the item
             let inv = &actor.inventory;
             if !inv.valid_slot(u.inventory_slot) {
                 return Err(MessageType::Error(format!(
@@ -94,24 +304,362 @@ pub fn is_valid(
                 )));
             }
 
+            let item = layer
+                .items
+                .get(&item_id.unwrap())
+                .expect("item should exist");
+            let target_kind = match item.kind {
+                ItemKind::Active(target_kind) => target_kind,
+                ItemKind::Passive(_) => {
+                    return Err(MessageType::Error(String::from(
+                        "Can't activate passive items",
+                    )))
+                }
+            };
+
+            if item.is_used_up() {
+                return Err(MessageType::Error(format!(
+                    "{} has no charges left",
+                    item.name
+                )));
+            }
+
+            if let Err(TargetingError::OutOfRange) =
+                targeting::get_targeted_squares(u.target, target_kind, actor.get_id(), layer)
+            {
+                return Err(MessageType::Error(String::from("Target is out of range")));
+            }
+
+            Ok(())
+        }
+        Action::PickUp(p) => {
+            check_slot_exists(actor, p.inventory_slot)?;
+
+            if actor.inventory.get_item(p.inventory_slot).is_some() {
+                return Err(MessageType::Error(format!(
+                    "Inventory slot {} is not empty",
+                    p.inventory_slot
+                )));
+            }
+
+            match layer.item_positions.get_position(p.item) {
+                Some(square) if square == actor.get_position() => Ok(()),
+                _ => Err(MessageType::Error(String::from(
+                    "There is no such item on the floor here",
+                ))),
+            }
+        }
+        Action::Loot(l) => {
+            check_slot_exists(actor, l.inventory_slot)?;
+
+            if actor.inventory.get_item(l.inventory_slot).is_some() {
+                return Err(MessageType::Error(format!(
+                    "Inventory slot {} is not empty",
+                    l.inventory_slot
+                )));
+            }
+
+            let corpse = match layer.creatures.get(&l.corpse) {
+                Some(c) if c.is_dead() => c,
+                _ => return Err(MessageType::Error(String::from("There is no such corpse"))),
+            };
+
+            if gridalgos::distance(actor.get_position(), corpse.get_position()) > 1 {
+                return Err(MessageType::Error(String::from(
+                    "Can only loot corpses next to the creature",
+                )));
+            }
+
+            if corpse.inventory.find_item(l.item).is_none() {
+                return Err(MessageType::Error(String::from(
+                    "The corpse does not carry that item",
+                )));
+            }
+
+            Ok(())
+        }
+        Action::Drop(d) => {
+            check_slot_has_item(actor, d.inventory_slot)?;
+            Ok(())
+        }
+        Action::SwapSlots(s) => {
+            check_slot_exists(actor, s.first_slot)?;
+            check_slot_exists(actor, s.second_slot)?;
+
+            if s.first_slot == s.second_slot {
+                return Err(MessageType::Error(String::from(
+                    "Can't swap an inventory slot with itself",
+                )));
+            }
+
+            Ok(())
+        }
+        Action::Equip(e) => {
+            let item_id = check_slot_has_item(actor, e.inventory_slot)?;
+            let item = layer.items.get(&item_id).expect("item should exist");
+
+            let params = match &item.kind {
+                ItemKind::Passive(params) => params,
+                ItemKind::Active(_) => {
+                    return Err(MessageType::Error(format!(
+                        "{} can't be equipped",
+                        item.name
+                    )))
+                }
+            };
+
+            if actor.equipment.is_equipped(item_id) {
+                return Err(MessageType::Error(format!(
+                    "{} is already equipped",
+                    item.name
+                )));
+            }
+
+            if actor.equipment.is_full(params.slot) {
+                return Err(MessageType::Error(format!(
+                    "{} slot is full, unequip something first",
+                    params.slot
+                )));
+            }
+
             Ok(())
         }
+        Action::Unequip(e) => {
+            let item_id = check_slot_has_item(actor, e.inventory_slot)?;
+
+            if !actor.equipment.is_equipped(item_id) {
+                return Err(MessageType::Error(format!(
+                    "Item in inventory slot {} is not equipped",
+                    e.inventory_slot
+                )));
+            }
+
+            Ok(())
+        }
+        Action::Interact(i) => {
+            let tile = match layer.grid.get_tile(i.target) {
+                Some(t) if t.is_interactable() => t,
+                _ => {
+                    return Err(MessageType::Error(String::from(
+                        "There is nothing to interact with there",
+                    )))
+                }
+            };
+
+            if gridalgos::distance(actor.get_position(), i.target) != 1 {
+                return Err(MessageType::Error(String::from(
+                    "Can only interact with adjacent squares",
+                )));
+            }
+
+            match tile {
+                Tile::Door(DoorState::Locked) => {
+                    Err(MessageType::Error(String::from("The door is locked")))
+                }
+                Tile::Door(DoorState::Open)
+                    if !layer.get_living_creatures_at(i.target).is_empty() =>
+                {
+                    Err(MessageType::Error(String::from(
+                        "Someone is standing in the doorway",
+                    )))
+                }
+                Tile::Lever(LeverState::Pulled) => Err(MessageType::Error(String::from(
+                    "The lever has already been pulled",
+                ))),
+                _ => Ok(()),
+            }
+        }
+        Action::Disarm(d) => {
+            let trap = layer
+                .get_trap_at(d.target)
+                .and_then(|t_id| layer.traps.get(&t_id));
+
+            match trap {
+                Some(t) if !t.hidden => (),
+                _ => {
+                    return Err(MessageType::Error(String::from(
+                        "There is no known trap there",
+                    )))
+                }
+            }
+
+            if gridalgos::distance(actor.get_position(), d.target) > 1 {
+                return Err(MessageType::Error(String::from(
+                    "Can only disarm traps next to the creature",
+                )));
+            }
+
+            Ok(())
+        }
+        Action::Dig(d) => {
+            if !layer.grid.destructible_square(d.target) {
+                return Err(MessageType::Error(String::from(
+                    "There is nothing to break there",
+                )));
+            }
+
+            if gridalgos::distance(actor.get_position(), d.target) != 1 {
+                return Err(MessageType::Error(String::from(
+                    "Can only dig into adjacent squares",
+                )));
+            }
+
+            Ok(())
+        }
+        Action::Stabilize(s) => {
+            if get_dying_creature_at(s.target, layer).is_none() {
+                return Err(MessageType::Error(String::from(
+                    "There is no one dying there",
+                )));
+            }
+
+            if gridalgos::distance(actor.get_position(), s.target) > 1 {
+                return Err(MessageType::Error(String::from(
+                    "Can only tend to creatures next to the creature",
+                )));
+            }
+
+            Ok(())
+        }
+    }
+}
+
+fn get_dying_creature_at(square: GridSquare, layer: &Layer) -> Option<u128> {
+    layer
+        .get_downed_creatures_at(square)
+        .into_iter()
+        .find(|c_id| matches!(layer.creatures[c_id].downed, Some(DownedState::Dying(_))))
+}
+
+// Digging hits as hard as the creature's strongest melee weapon
+fn get_dig_damage(actor: &Creature, layer: &Layer) -> i32 {
+    let inv = &actor.inventory;
+
+    let weapon_damage = (0..inv.len())
+        .filter_map(|slot| inv.get_item(slot))
+        .filter(|i_id| {
+            let item = layer.items.get(i_id).expect("item should exist");
+            matches!(item.kind, ItemKind::Active(TargetKind::Square(p)) if p.range == 1)
+        })
+        .filter_map(|i_id| {
+            let effect = layer
+                .effects
+                .get(&i_id)
+                .expect("item should have an effect");
+            effect
+                .stats
+                .dice
+                .contains_key("damage")
+                .then(|| effect.get_dice("damage"))
+        })
+        .max_by(|a, b| a.average().total_cmp(&b.average()));
+
+    match weapon_damage {
+        Some(dice) => dice.roll(&mut rand::thread_rng()).total,
+        None => constants::UNARMED_DIG_DAMAGE,
+    }
+}
+
+// Used up items are removed from the inventory, but they are kept in
+// the layer so that messages can still refer to them.
+pub(crate) fn use_charge(item_id: u128, owner_id: u128, inventory_slot: usize, layer: &mut Layer) {
+    let item = layer.items.get_mut(&item_id).expect("item should exist");
+
+    let charges = match item.charges.as_mut() {
+        Some(c) => c,
+        None => return,
+    };
+
+    *charges -= 1;
+
+    if item.is_used_up() {
+        let owner = layer
+            .creatures
+            .get_mut(&owner_id)
+            .expect("owner should be a valid creature");
+
+        owner.inventory.drop_item(inventory_slot);
     }
 }
 
-pub fn execute(action: &Action, actor_id: u128, layer: &mut Layer) -> Option<MessageType> {
+fn search_for_traps(actor_id: u128, layer: &mut Layer, messages: &mut Vec<String>) -> Vec<u128> {
+    let noticed = trap::search(actor_id, layer);
+
+    for t_id in &noticed {
+        let actor = layer.creatures.get(&actor_id).unwrap();
+        let trap = layer.traps.get(t_id).unwrap();
+        messages.push(format!("{} notices a {}", actor.name, trap.kind));
+    }
+
+    noticed
+}
+
+// The creature walks the path one square at a time. Traps on the way are
+// set off, and the creature stops in front of a trap it notices.
+fn execute_move(m: &MoveAction, actor_id: u128, layer: &mut Layer) -> Vec<String> {
+    let actor = layer.creatures.get(&actor_id).unwrap();
+    let movement_speed = actor.stats.movement_speed.get_value(actor.stats.level);
+
+    let path =
+        gridalgos::find_shortest_path(actor.get_position(), m.destination, movement_speed, layer)
+            .expect("the destination should have been checked to be reachable");
+
+    let mut messages: Vec<String> = Vec::new();
+
+    for square in path.into_iter().skip(1) {
+        let noticed = search_for_traps(actor_id, layer, &mut messages);
+
+        let trap_id = layer.get_trap_at(square);
+        if trap_id.is_some_and(|t_id| noticed.contains(&t_id)) {
+            break;
+        }
+
+        layer.move_creature(actor_id, square);
+
+        if let Some(t_id) = trap_id {
+            if let MessageType::Info(msg) = trap::trigger(t_id, square, layer) {
+                messages.push(msg);
+            }
+        }
+
+        let actor = layer.creatures.get(&actor_id).unwrap();
+        if !actor.is_alive() || actor.stats.movement_speed.get_value(actor.stats.level) == 0 {
+            break;
+        }
+    }
+
+    // Look around once more at the final square
+    search_for_traps(actor_id, layer, &mut messages);
+
+    messages
+}
+
+pub fn execute(
+    action: &Action,
+    turn: &Turn,
+    actor_id: u128,
+    layer: &mut Layer,
+) -> Option<MessageType> {
     let actor = layer
         .creatures
         .get_mut(&actor_id)
         .expect("actor should be a valid creature");
 
     match action {
-        Action::Idle => return None,
+        Action::Idle | Action::EndTurn => return None,
+        Action::Delay => Some(MessageType::Info(format!("{} delays", actor.name))),
         Action::Move(m) => {
             println!("position before move: {}", actor.get_position());
-            actor.set_position(&m.destination);
+            let messages = execute_move(m, actor_id, layer);
+
+            let actor = layer.creatures.get(&actor_id).unwrap();
             println!("position after move: {}", actor.get_position());
-            return None;
+
+            if messages.is_empty() {
+                return None;
+            }
+
+            Some(MessageType::Info(messages.join(". ")))
         }
         Action::UseItem(u) => {
             let inv = &actor.inventory;
@@ -124,13 +672,462 @@ pub fn execute(action: &Action, actor_id: u128, layer: &mut Layer) -> Option<Mes
                 .get(&item_id)
                 .expect("an item should have an effect");
 
+            // Items that deal damage are the ones that attack, so healing
+            // and other effects don't take the penalty
+            let is_attack = effect.stats.damage_type.is_some();
+            if is_attack {
+                let penalty = turn.get_multiple_attack_penalty();
+                for stat in ATTACK_STATS {
+                    actor
+                        .stats
+                        .get_stat_mut(stat)
+                        .apply_additive_modifier(constants::MULTIPLE_ATTACK_PENALTY_KEY, penalty);
+                }
+            }
+
             let effect_result = (effect.apply)(item_id, actor_id, u.target.clone(), layer);
 
+            if is_attack {
+                let actor = layer
+                    .creatures
+                    .get_mut(&actor_id)
+                    .expect("actor should be a valid creature");
+                for stat in ATTACK_STATS {
+                    actor
+                        .stats
+                        .get_stat_mut(stat)
+                        .remove_modifier(constants::MULTIPLE_ATTACK_PENALTY_KEY);
+                }
+            }
+
             if let Some(e) = effect_result.ongoing_effect {
                 layer.ongoing_effects.insert(e.get_id(), e);
             }
 
+            use_charge(item_id, actor_id, u.inventory_slot, layer);
+
             return Some(effect_result.message);
         }
+        Action::PickUp(p) => {
+            actor.inventory.replace_item(p.inventory_slot, p.item);
+            let actor_name = actor.name.clone();
+
+            layer.take_item(p.item);
+            let item = layer.items.get(&p.item).expect("item should exist");
+
+            Some(MessageType::Info(format!(
+                "{} picks up {}",
+                actor_name, item.name
+            )))
+        }
+        Action::Loot(l) => {
+            actor.inventory.replace_item(l.inventory_slot, l.item);
+            let actor_name = actor.name.clone();
+
+            let corpse = layer
+                .creatures
+                .get_mut(&l.corpse)
+                .expect("corpse should exist");
+            let corpse_slot = corpse
+                .inventory
+                .find_item(l.item)
+                .expect("the corpse should have been checked to carry the item");
+            corpse.inventory.drop_item(corpse_slot);
+            corpse.unequip_item(l.item);
+
+            let corpse_name = corpse.name.clone();
+            let item = layer.items.get(&l.item).expect("item should exist");
+
+            Some(MessageType::Info(format!(
+                "{} takes {} from the corpse of {}",
+                actor_name, item.name, corpse_name
+            )))
+        }
+        Action::Drop(d) => {
+            let item_id = actor
+                .inventory
+                .drop_item(d.inventory_slot)
+                .expect("the slot should exist and contain an item");
+            actor.unequip_item(item_id);
+
+            let actor_name = actor.name.clone();
+            let position = actor.get_position();
+
+            layer.place_item(item_id, position);
+            let item = layer.items.get(&item_id).expect("item should exist");
+
+            Some(MessageType::Info(format!(
+                "{} drops {}",
+                actor_name, item.name
+            )))
+        }
+        Action::SwapSlots(s) => {
+            actor.inventory.swap_items(s.first_slot, s.second_slot);
+            None
+        }
+        Action::Equip(e) => {
+            let item_id = actor
+                .inventory
+                .get_item(e.inventory_slot)
+                .expect("the slot should exist and contain an item");
+            let item = layer.items.get(&item_id).expect("item should exist");
+
+            if let ItemKind::Passive(params) = &item.kind {
+                actor.equip_item(item_id, params);
+            }
+
+            Some(MessageType::Info(format!(
+                "{} equips {}",
+                actor.name, item.name
+            )))
+        }
+        Action::Unequip(e) => {
+            let item_id = actor
+                .inventory
+                .get_item(e.inventory_slot)
+                .expect("the slot should exist and contain an item");
+            let item = layer.items.get(&item_id).expect("item should exist");
+
+            actor.unequip_item(item_id);
+
+            Some(MessageType::Info(format!(
+                "{} unequips {}",
+                actor.name, item.name
+            )))
+        }
+        Action::Disarm(d) => {
+            let trap_id = layer
+                .get_trap_at(d.target)
+                .expect("the trap should have been checked to exist");
+
+            Some(trap::disarm(actor_id, trap_id, layer))
+        }
+        Action::Stabilize(s) => {
+            let target_id = get_dying_creature_at(s.target, layer)
+                .expect("the target should have been checked to be dying");
+
+            Some(downed::stabilize(actor_id, target_id, layer))
+        }
+        Action::Dig(d) => {
+            let actor_name = actor.name.clone();
+            let actor = layer.creatures.get(&actor_id).unwrap();
+            let damage = get_dig_damage(actor, layer);
+            let tile = layer
+                .grid
+                .get_tile(d.target)
+                .expect("target should be valid");
+            let name = match tile {
+                Tile::Door(_) => "door",
+                _ => "wall",
+            };
+
+            if layer.grid.damage_tile(d.target, damage) {
+                Some(MessageType::Info(format!(
+                    "{} breaks through the {}",
+                    actor_name, name
+                )))
+            } else {
+                let hp_left = layer.grid.get_tile_hp(d.target).unwrap();
+                Some(MessageType::Info(format!(
+                    "{} hacks at the {} ({} damage, {} left)",
+                    actor_name, name, damage, hp_left
+                )))
+            }
+        }
+        Action::Interact(i) => {
+            let actor_name = actor.name.clone();
+            let tile = layer
+                .grid
+                .get_tile(i.target)
+                .expect("target should be valid");
+
+            match tile {
+                Tile::Door(DoorState::Open) => {
+                    layer.grid.set_tile(i.target, Tile::Door(DoorState::Closed));
+                    Some(MessageType::Info(format!("{} closes the door", actor_name)))
+                }
+                Tile::Door(DoorState::Closed) => {
+                    layer.grid.set_tile(i.target, Tile::Door(DoorState::Open));
+                    Some(MessageType::Info(format!("{} opens the door", actor_name)))
+                }
+                Tile::Lever(LeverState::Up) => {
+                    layer
+                        .grid
+                        .set_tile(i.target, Tile::Lever(LeverState::Pulled));
+
+                    let mut n_unlocked = 0;
+                    for square in gridalgos::emanation(i.target, constants::LEVER_RADIUS) {
+                        if layer.grid.get_tile(square) == Some(Tile::Door(DoorState::Locked)) {
+                            layer.grid.set_tile(square, Tile::Door(DoorState::Open));
+                            n_unlocked += 1;
+                        }
+                    }
+
+                    Some(MessageType::Info(format!(
+                        "{} pulls the lever, {} doors swing open",
+                        actor_name, n_unlocked
+                    )))
+                }
+                _ => None,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::{Behavior, AI};
+    use crate::creature::statistics::Statistics;
+    use crate::item::catalog::ItemCatalog;
+    use crate::item::statistics::Rarity;
+    use crate::trap::{Trap, TrapKind};
+
+    fn inactive_ai(c_id: u128) -> AI {
+        AI::new(c_id, Behavior::Inactive, Behavior::Inactive)
+    }
+
+    fn taken(cost: i32, is_attack: bool) -> TakenAction {
+        TakenAction {
+            action: Action::Idle,
+            cost,
+            is_attack,
+        }
+    }
+
+    #[test]
+    fn test_action_budget() {
+        let position = GridSquare { y: 0, x: 0 };
+        let actor = Creature::new(String::from("actor"), position, Statistics::get_default());
+        let max_actions = actor.stats.n_actions.get_value(actor.stats.level);
+
+        let mut turn = Turn::new(false);
+        assert_eq!(turn.get_actions_left(&actor), max_actions);
+        assert_eq!(turn.get_multiple_attack_penalty(), 0);
+
+        turn.prev_actions.push(taken(2, true));
+        assert_eq!(turn.get_actions_left(&actor), max_actions - 2);
+        assert_eq!(
+            turn.get_multiple_attack_penalty(),
+            -constants::MULTIPLE_ATTACK_PENALTY
+        );
+
+        turn.prev_actions.push(taken(0, false));
+        turn.prev_actions.push(taken(1, true));
+        turn.prev_actions.push(taken(1, true));
+        assert_eq!(turn.get_actions_left(&actor), max_actions - 4);
+        assert_eq!(
+            turn.get_multiple_attack_penalty(),
+            -2 * constants::MULTIPLE_ATTACK_PENALTY
+        );
+    }
+
+    #[test]
+    fn test_delay() {
+        let layer = Layer::new(5, 5);
+        let position = GridSquare { y: 0, x: 0 };
+        let actor = Creature::new(String::from("actor"), position, Statistics::get_default());
+
+        let mut turn = Turn::new(false);
+        assert!(is_valid(&Action::Delay, &turn, &actor, &layer).is_ok());
+
+        turn.prev_actions.push(taken(1, false));
+        assert!(is_valid(&Action::Delay, &turn, &actor, &layer).is_err());
+
+        let delayed_turn = Turn::new(true);
+        assert!(is_valid(&Action::Delay, &delayed_turn, &actor, &layer).is_err());
+        assert!(is_valid(&Action::EndTurn, &delayed_turn, &actor, &layer).is_ok());
+    }
+
+    #[test]
+    fn test_move_triggers_traps() {
+        let mut layer = Layer::new(1, 5);
+        let position = GridSquare { y: 0, x: 0 };
+        let actor = Creature::new(String::from("actor"), position, Statistics::get_default());
+        let actor_id = actor.get_id();
+        layer.add_creature(actor, inactive_ai(actor_id));
+
+        let trap_square = GridSquare { y: 0, x: 1 };
+        let (trap, effect) = Trap::new(TrapKind::Spikes, 1, trap_square);
+        layer.add_trap(trap, effect);
+
+        let destination = GridSquare { y: 0, x: 3 };
+        let action = Action::Move(MoveAction { destination });
+        let turn = Turn::new(false);
+
+        let actor = layer.creatures.get(&actor_id).unwrap();
+        assert!(is_valid(&action, &turn, actor, &layer).is_ok());
+
+        let message = execute(&action, &turn, actor_id, &mut layer);
+        assert!(message.is_some());
+        assert!(layer.traps.is_empty());
+    }
+
+    #[test]
+    fn test_loot_corpse() {
+        let mut layer = Layer::new(1, 5);
+        let catalog = ItemCatalog::parse(include_str!("../../data/items.ron")).unwrap();
+
+        let actor = Creature::new(
+            String::from("actor"),
+            GridSquare { y: 0, x: 0 },
+            Statistics::get_default(),
+        );
+        let actor_id = actor.get_id();
+        layer.add_creature(actor, inactive_ai(actor_id));
+
+        let mut corpse = Creature::new(
+            String::from("corpse"),
+            GridSquare { y: 0, x: 1 },
+            Statistics::get_default(),
+        );
+        let corpse_id = corpse.get_id();
+        let (item, effect) = catalog.create_item("Dagger", 1, Rarity::Common).unwrap();
+        let item_id = item.get_id();
+        corpse.inventory.replace_item(0, item_id);
+        layer.add_item(item, effect);
+        layer.add_creature(corpse, inactive_ai(corpse_id));
+
+        let action = Action::Loot(LootAction {
+            corpse: corpse_id,
+            item: item_id,
+            inventory_slot: 0,
+        });
+        let turn = Turn::new(false);
+
+        // The corpse has to be dead first
+        let actor = layer.creatures.get(&actor_id).unwrap();
+        assert!(is_valid(&action, &turn, actor, &layer).is_err());
+
+        let corpse = layer.creatures.get_mut(&corpse_id).unwrap();
+        let hp = corpse.stats.current_hp;
+        corpse.change_hp(-hp);
+
+        let actor = layer.creatures.get(&actor_id).unwrap();
+        assert!(is_valid(&action, &turn, actor, &layer).is_ok());
+        execute(&action, &turn, actor_id, &mut layer);
+
+        assert_eq!(
+            layer.creatures[&actor_id].inventory.get_item(0),
+            Some(item_id)
+        );
+        assert!(layer.creatures[&corpse_id]
+            .inventory
+            .find_item(item_id)
+            .is_none());
+    }
+
+    #[test]
+    fn test_equipment_modifiers() {
+        let mut layer = Layer::new(1, 5);
+        let catalog = ItemCatalog::parse(include_str!("../../data/items.ron")).unwrap();
+
+        let mut actor = Creature::new(
+            String::from("actor"),
+            GridSquare { y: 0, x: 2 },
+            Statistics::get_default(),
+        );
+        let actor_id = actor.get_id();
+        let (item, effect) = catalog
+            .create_item("Wooden shield", 1, Rarity::Common)
+            .unwrap();
+        let item_id = item.get_id();
+        actor.inventory.replace_item(0, item_id);
+        layer.add_item(item, effect);
+        layer.add_creature(actor, inactive_ai(actor_id));
+
+        let armor_class = |layer: &Layer| {
+            let actor = &layer.creatures[&actor_id];
+            actor.stats.armor_class.get_value(actor.stats.level)
+        };
+        let base_ac = armor_class(&layer);
+        let turn = Turn::new(false);
+        let equip = Action::Equip(EquipAction { inventory_slot: 0 });
+
+        execute(&equip, &turn, actor_id, &mut layer);
+        assert_eq!(armor_class(&layer), base_ac + 1);
+
+        let unequip = Action::Unequip(EquipAction { inventory_slot: 0 });
+        execute(&unequip, &turn, actor_id, &mut layer);
+        assert_eq!(armor_class(&layer), base_ac);
+        assert!(!layer.creatures[&actor_id].equipment.is_equipped(item_id));
+
+        // Dropping an equipped item takes it off as well
+        execute(&equip, &turn, actor_id, &mut layer);
+        let drop = Action::Drop(DropAction { inventory_slot: 0 });
+        execute(&drop, &turn, actor_id, &mut layer);
+        assert_eq!(armor_class(&layer), base_ac);
+        assert!(!layer.creatures[&actor_id].equipment.is_equipped(item_id));
+    }
+
+    #[test]
+    fn test_pick_up_drop_and_swap() {
+        let mut layer = Layer::new(1, 5);
+        let catalog = ItemCatalog::parse(include_str!("../../data/items.ron")).unwrap();
+        let position = GridSquare { y: 0, x: 2 };
+
+        let actor = Creature::new(String::from("actor"), position, Statistics::get_default());
+        let actor_id = actor.get_id();
+        layer.add_creature(actor, inactive_ai(actor_id));
+
+        let add_item = |name: &str, layer: &mut Layer, square: GridSquare| {
+            let (item, effect) = catalog.create_item(name, 1, Rarity::Common).unwrap();
+            let item_id = item.get_id();
+            layer.add_item(item, effect);
+            layer.place_item(item_id, square);
+            item_id
+        };
+        let dagger = add_item("Dagger", &mut layer, position);
+        let far_shield = add_item("Wooden shield", &mut layer, GridSquare { y: 0, x: 4 });
+
+        let turn = Turn::new(false);
+        let pick_up = |item: u128, inventory_slot: usize| {
+            Action::PickUp(PickUpAction {
+                item,
+                inventory_slot,
+            })
+        };
+
+        // Only items on the actor's own square can be picked up
+        let actor = &layer.creatures[&actor_id];
+        assert!(is_valid(&pick_up(far_shield, 0), &turn, actor, &layer).is_err());
+        assert!(is_valid(&pick_up(dagger, 0), &turn, actor, &layer).is_ok());
+        execute(&pick_up(dagger, 0), &turn, actor_id, &mut layer);
+        assert!(layer.item_positions.get_position(dagger).is_none());
+
+        // Fill the rest of the inventory
+        let n_slots = layer.creatures[&actor_id].inventory.len();
+        for slot in 1..n_slots {
+            let item_id = add_item("Dagger", &mut layer, position);
+            execute(&pick_up(item_id, slot), &turn, actor_id, &mut layer);
+        }
+
+        let shield = add_item("Wooden shield", &mut layer, position);
+        let actor = &layer.creatures[&actor_id];
+        for slot in 0..n_slots {
+            assert!(is_valid(&pick_up(shield, slot), &turn, actor, &layer).is_err());
+        }
+
+        let swap = Action::SwapSlots(SwapSlotsAction {
+            first_slot: 0,
+            second_slot: n_slots - 1,
+        });
+        let last_item = actor.inventory.get_item(n_slots - 1).unwrap();
+        assert!(is_valid(&swap, &turn, actor, &layer).is_ok());
+        execute(&swap, &turn, actor_id, &mut layer);
+
+        let inventory = &layer.creatures[&actor_id].inventory;
+        assert_eq!(inventory.get_item(0), Some(last_item));
+        assert_eq!(inventory.get_item(n_slots - 1), Some(dagger));
+
+        let drop = Action::Drop(DropAction {
+            inventory_slot: n_slots - 1,
+        });
+        execute(&drop, &turn, actor_id, &mut layer);
+        assert!(layer.creatures[&actor_id]
+            .inventory
+            .get_item(n_slots - 1)
+            .is_none());
+        assert_eq!(layer.item_positions.get_position(dagger), Some(position));
     }
 }
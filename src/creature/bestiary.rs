@@ -0,0 +1,438 @@
+use crate::ai::{Behavior, AI};
+use crate::constants;
+use crate::creature::statistics::*;
+use crate::creature::Creature;
+use crate::damage::DamageType;
+use crate::data::{self, DataError};
+use crate::grid::GridSquare;
+use crate::item::catalog::ItemCatalog;
+use crate::item::generator;
+use crate::item::ItemKind;
+use crate::stat::Proficiency;
+use crate::world::Layer;
+use rand::distributions::WeightedIndex;
+use rand::prelude::*;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::Path;
+
+pub const BESTIARY_FILE: &str = "bestiary.ron";
+
+// Stats that are left out of a definition are moderate.
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct StatProficiencies {
+    pub max_hp: Proficiency,
+
+    pub armor_class: Proficiency,
+    pub fortitude_dc: Proficiency,
+    pub reflex_dc: Proficiency,
+    pub will_dc: Proficiency,
+
+    pub melee_attack: Proficiency,
+    pub ranged_attack: Proficiency,
+    pub magic_attack: Proficiency,
+
+    pub movement_speed: Proficiency,
+    pub initiative: Proficiency,
+
+    pub n_actions: Proficiency,
+
+    pub inventory_slots: Proficiency,
+
+    // Creatures have no resistances, weaknesses or immunities by default
+    pub resistances: HashMap<DamageType, Proficiency>,
+    pub weaknesses: HashMap<DamageType, Proficiency>,
+    pub immunities: Vec<DamageType>,
+}
+
+impl Default for StatProficiencies {
+    fn default() -> Self {
+        Self {
+            max_hp: Proficiency::Moderate,
+
+            armor_class: Proficiency::Moderate,
+            fortitude_dc: Proficiency::Moderate,
+            reflex_dc: Proficiency::Moderate,
+            will_dc: Proficiency::Moderate,
+
+            melee_attack: Proficiency::Moderate,
+            ranged_attack: Proficiency::Moderate,
+            magic_attack: Proficiency::Moderate,
+
+            movement_speed: Proficiency::Moderate,
+            initiative: Proficiency::Moderate,
+
+            n_actions: Proficiency::Moderate,
+
+            inventory_slots: Proficiency::Moderate,
+
+            resistances: HashMap::new(),
+            weaknesses: HashMap::new(),
+            immunities: Vec::new(),
+        }
+    }
+}
+
+impl StatProficiencies {
+    pub fn to_statistics(&self, level: i32) -> Statistics {
+        Statistics {
+            level,
+
+            max_hp: new_hp(self.max_hp),
+
+            armor_class: new_defense(self.armor_class),
+            fortitude_dc: new_defense(self.fortitude_dc),
+            reflex_dc: new_defense(self.reflex_dc),
+            will_dc: new_defense(self.will_dc),
+
+            melee_attack: new_attack(self.melee_attack),
+            ranged_attack: new_attack(self.ranged_attack),
+            magic_attack: new_attack(self.magic_attack),
+
+            movement_speed: new_speed(self.movement_speed),
+            initiative: new_initiative(self.initiative),
+
+            n_actions: new_actions(self.n_actions),
+
+            inventory_slots: new_inventory(self.inventory_slots),
+
+            resistances: self
+                .resistances
+                .iter()
+                .map(|(t, prof)| (*t, new_resistance(*prof)))
+                .collect(),
+            weaknesses: self
+                .weaknesses
+                .iter()
+                .map(|(t, prof)| (*t, new_resistance(*prof)))
+                .collect(),
+            immunities: self.immunities.clone(),
+
+            ..Statistics::get_default()
+        }
+    }
+}
+
+// An item the creature may carry as loot. The rarity is rolled from the
+// rarities the item allows, so rare versions with more affixes drop less
+// often.
+#[derive(Deserialize)]
+pub struct LootEntry {
+    pub item: String,
+    // Probability of the creature carrying the item at all
+    pub chance: f64,
+}
+
+#[derive(Deserialize)]
+pub struct CreatureDefinition {
+    pub name: String,
+    #[serde(default)]
+    pub stats: StatProficiencies,
+
+    pub wander_behavior: Behavior,
+    pub combat_behavior: Behavior,
+
+    // Names of the items the creature carries, in inventory order
+    #[serde(default)]
+    pub items: Vec<String>,
+    // Rolled when the creature spawns. Loot goes to the inventory slots
+    // that are left over after the items.
+    #[serde(default)]
+    pub loot: Vec<LootEntry>,
+
+    // Inclusive range of levels the creature can spawn at
+    pub level_range: (i32, i32),
+    // Relative spawn probability by layer depth. The creature does not
+    // spawn at depths that are not listed.
+    pub spawn_weights: HashMap<i32, u32>,
+}
+
+impl CreatureDefinition {
+    pub fn get_spawn_weight(&self, depth: i32) -> u32 {
+        *self.spawn_weights.get(&depth).unwrap_or(&0)
+    }
+
+    pub fn choose_level<R: Rng>(&self, rng: &mut R) -> i32 {
+        let (min_level, max_level) = self.level_range;
+        rng.gen_range(min_level..=max_level)
+    }
+
+    // Create a creature from this definition and add it to the layer
+    // together with its items and loot. Returns the id of the new creature.
+    pub fn spawn<R: Rng>(
+        &self,
+        level: i32,
+        position: GridSquare,
+        catalog: &ItemCatalog,
+        layer: &mut Layer,
+        rng: &mut R,
+    ) -> u128 {
+        let stats = self.stats.to_statistics(level);
+        let mut creature = Creature::new(self.name.clone(), position, stats);
+
+        for (slot, item_name) in self.items.iter().enumerate() {
+            if !creature.inventory.valid_slot(slot) {
+                break;
+            }
+
+            // Carried items have the lowest rarity the item allows
+            let definition = catalog
+                .get(item_name)
+                .expect("bestiary items should have been validated");
+            let rarity = *definition
+                .rarities
+                .iter()
+                .min_by_key(|r| r.get_tier())
+                .expect("items should have been validated to have rarities");
+            let (item, effect) = definition.create(level, rarity);
+
+            // Items that don't fit in their equipment slot are only carried
+            creature.inventory.replace_item(slot, item.get_id());
+            if let ItemKind::Passive(params) = &item.kind {
+                if !creature.equipment.is_full(params.slot) {
+                    creature.equip_item(item.get_id(), params);
+                }
+            }
+
+            layer.add_item(item, effect);
+        }
+
+        for entry in &self.loot {
+            if !rng.gen_bool(entry.chance) {
+                continue;
+            }
+
+            let slot = match creature.inventory.find_empty_slot() {
+                Some(slot) => slot,
+                None => break,
+            };
+
+            let definition = catalog
+                .get(&entry.item)
+                .expect("loot should have been validated");
+            let rarity = definition.choose_rarity(rng);
+            let (item, effect) = generator::generate(definition, level, rarity, rng);
+
+            creature.inventory.replace_item(slot, item.get_id());
+            layer.add_item(item, effect);
+        }
+
+        let id = creature.get_id();
+        let c_ai = AI::new(id, self.wander_behavior, self.combat_behavior);
+        layer.add_creature(creature, c_ai);
+
+        id
+    }
+
+    fn validate(&self, catalog: &ItemCatalog) -> Result<(), DataError> {
+        let invalid = |msg: String| Err(DataError::Invalid(format!("{}: {}", self.name, msg)));
+
+        if self.name.is_empty() {
+            return Err(DataError::Invalid(String::from(
+                "creature definition has no name",
+            )));
+        }
+
+        let (min_level, max_level) = self.level_range;
+        if min_level > max_level {
+            return invalid(format!("empty level range {}..{}", min_level, max_level));
+        }
+        if min_level < constants::MIN_LEVEL {
+            return invalid(format!(
+                "level {} is below the minimum level {}",
+                min_level,
+                constants::MIN_LEVEL
+            ));
+        }
+
+        for behavior in [self.wander_behavior, self.combat_behavior] {
+            if let Behavior::PlayerControlled(_) = behavior {
+                return invalid(String::from("creatures can't be player-controlled"));
+            }
+        }
+
+        for item_name in &self.items {
+            if catalog.get(item_name).is_none() {
+                return invalid(format!("unknown item \"{}\"", item_name));
+            }
+        }
+
+        for entry in &self.loot {
+            if catalog.get(&entry.item).is_none() {
+                return invalid(format!("unknown loot item \"{}\"", entry.item));
+            }
+
+            if !(entry.chance > 0.0 && entry.chance <= 1.0) {
+                return invalid(format!(
+                    "loot chance {} of \"{}\" is not between 0 and 1",
+                    entry.chance, entry.item
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+pub struct Bestiary {
+    creatures: Vec<CreatureDefinition>,
+}
+
+impl Bestiary {
+    // Creature items are checked against the given item catalog.
+    pub fn load(path: &Path, catalog: &ItemCatalog) -> Result<Self, DataError> {
+        let creatures: Vec<CreatureDefinition> = data::load(path)?;
+        Self::build(creatures, catalog)
+    }
+
+    pub fn load_default(catalog: &ItemCatalog) -> Result<Self, DataError> {
+        let path = Path::new(constants::DATA_DIRECTORY).join(BESTIARY_FILE);
+        Self::load(&path, catalog)
+    }
+
+    pub fn parse(bestiary: &str, catalog: &ItemCatalog) -> Result<Self, DataError> {
+        let creatures: Vec<CreatureDefinition> = data::parse(bestiary)?;
+        Self::build(creatures, catalog)
+    }
+
+    fn build(creatures: Vec<CreatureDefinition>, catalog: &ItemCatalog) -> Result<Self, DataError> {
+        let bestiary = Self { creatures };
+        bestiary.validate(catalog)?;
+
+        Ok(bestiary)
+    }
+
+    fn validate(&self, catalog: &ItemCatalog) -> Result<(), DataError> {
+        for (i, creature) in self.creatures.iter().enumerate() {
+            creature.validate(catalog)?;
+
+            if self.creatures[..i].iter().any(|c| c.name == creature.name) {
+                return Err(DataError::Invalid(format!(
+                    "creature \"{}\" is defined more than once",
+                    creature.name
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn get(&self, name: &str) -> Option<&CreatureDefinition> {
+        self.creatures.iter().find(|c| c.name == name)
+    }
+
+    pub fn get_all(&self) -> &Vec<CreatureDefinition> {
+        &self.creatures
+    }
+
+    // Choose a random creature that can spawn at the given depth,
+    // weighted by its spawn weight.
+    pub fn choose<R: Rng>(&self, depth: i32, rng: &mut R) -> Option<&CreatureDefinition> {
+        let weights: Vec<u32> = self
+            .creatures
+            .iter()
+            .map(|c| c.get_spawn_weight(depth))
+            .collect();
+
+        let distribution = WeightedIndex::new(&weights).ok()?;
+
+        Some(&self.creatures[distribution.sample(rng)])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::item::statistics::Rarity;
+    use rand_chacha::ChaCha20Rng;
+
+    const BESTIARY: &str = include_str!("../../data/bestiary.ron");
+    const CATALOG: &str = include_str!("../../data/items.ron");
+
+    fn parse(bestiary: &str) -> Result<Bestiary, DataError> {
+        let catalog = ItemCatalog::parse(CATALOG).unwrap();
+        Bestiary::parse(bestiary, &catalog)
+    }
+
+    #[test]
+    fn test_bestiary_is_valid() {
+        let bestiary = parse(BESTIARY);
+        assert!(bestiary.is_ok(), "{}", bestiary.err().unwrap());
+    }
+
+    #[test]
+    fn test_every_layer_has_creatures() {
+        let bestiary = parse(BESTIARY).unwrap();
+        let mut rng = ChaCha20Rng::seed_from_u64(0);
+
+        for depth in 0..constants::WORLD_LAYERS {
+            let creature = bestiary.choose(depth, &mut rng);
+            assert!(creature.is_some());
+            assert!(creature.unwrap().get_spawn_weight(depth) > 0);
+        }
+    }
+
+    #[test]
+    fn test_spawn_with_uncommon_item() {
+        let bestiary = r#"[(
+            name: "Goblin",
+            wander_behavior: Wandering,
+            combat_behavior: Inactive,
+            items: ["Boots of striding"],
+            level_range: (0, 1),
+            spawn_weights: {0: 1},
+        )]"#;
+        let bestiary = parse(bestiary).unwrap();
+        let catalog = ItemCatalog::parse(CATALOG).unwrap();
+        let mut layer = Layer::new(3, 3);
+        let mut rng = ChaCha20Rng::seed_from_u64(0);
+
+        let definition = bestiary.get("Goblin").unwrap();
+        let c_id = definition.spawn(1, GridSquare { y: 1, x: 1 }, &catalog, &mut layer, &mut rng);
+
+        let item_id = layer.creatures[&c_id].inventory.get_item(0).unwrap();
+        assert!(layer.effects[&item_id].stats.rarity == Rarity::Uncommon);
+    }
+
+    #[test]
+    fn test_invalid_definitions() {
+        let unknown_item = r#"[(
+            name: "Goblin",
+            wander_behavior: Wandering,
+            combat_behavior: Inactive,
+            items: ["Banana"],
+            level_range: (0, 1),
+            spawn_weights: {0: 1},
+        )]"#;
+        assert!(matches!(parse(unknown_item), Err(DataError::Invalid(_))));
+
+        let bad_level_range = r#"[(
+            name: "Goblin",
+            wander_behavior: Wandering,
+            combat_behavior: Inactive,
+            level_range: (3, 1),
+            spawn_weights: {0: 1},
+        )]"#;
+        assert!(matches!(parse(bad_level_range), Err(DataError::Invalid(_))));
+
+        let player_controlled = r#"[(
+            name: "Goblin",
+            wander_behavior: PlayerControlled(1),
+            combat_behavior: Inactive,
+            level_range: (0, 1),
+            spawn_weights: {0: 1},
+        )]"#;
+        assert!(parse(player_controlled).is_err());
+
+        let bad_loot_chance = r#"[(
+            name: "Goblin",
+            wander_behavior: Wandering,
+            combat_behavior: Inactive,
+            loot: [(item: "Dagger", chance: 1.5)],
+            level_range: (0, 1),
+            spawn_weights: {0: 1},
+        )]"#;
+        assert!(matches!(parse(bad_loot_chance), Err(DataError::Invalid(_))));
+    }
+}
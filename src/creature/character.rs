@@ -0,0 +1,285 @@
+use crate::creature::statistics::*;
+use crate::creature::Creature;
+use crate::grid::GridSquare;
+use crate::item::catalog::ItemCatalog;
+use crate::item::effect::Effect;
+use crate::item::statistics::Rarity;
+use crate::item::{Item, ItemKind};
+use crate::stat::Proficiency;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+// Ancestry decides what the character's body is capable of: how much
+// punishment it takes, how fast it moves and how much it can carry.
+#[derive(Clone, Copy, Deserialize, PartialEq, Serialize)]
+pub enum Ancestry {
+    Human,
+    Elf,
+    Dwarf,
+    Halfling,
+}
+
+// Class decides what the character has trained for: how it attacks,
+// how it defends and which items it starts with.
+#[derive(Clone, Copy, Deserialize, PartialEq, Serialize)]
+pub enum Class {
+    Fighter,
+    Archer,
+    Mage,
+}
+
+#[derive(Clone, Copy, Deserialize, Serialize)]
+pub struct CharacterOptions {
+    pub ancestry: Ancestry,
+    pub class: Class,
+}
+
+impl Ancestry {
+    pub fn all() -> Vec<Ancestry> {
+        vec![
+            Ancestry::Human,
+            Ancestry::Elf,
+            Ancestry::Dwarf,
+            Ancestry::Halfling,
+        ]
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Ancestry::Human => "Adaptable and well-equipped, carries more than most",
+            Ancestry::Elf => "Quick on their feet and quick to react, but frail",
+            Ancestry::Dwarf => "Tough and heavily laden, but slow",
+            Ancestry::Halfling => "Nimble and alert, with little room in their packs",
+        }
+    }
+
+    fn apply(&self, stats: &mut Statistics) {
+        let (hp, speed, initiative, inventory) = match self {
+            Ancestry::Human => (
+                Proficiency::Moderate,
+                Proficiency::Moderate,
+                Proficiency::Moderate,
+                Proficiency::High,
+            ),
+            Ancestry::Elf => (
+                Proficiency::Low,
+                Proficiency::High,
+                Proficiency::High,
+                Proficiency::Moderate,
+            ),
+            Ancestry::Dwarf => (
+                Proficiency::High,
+                Proficiency::Low,
+                Proficiency::Low,
+                Proficiency::High,
+            ),
+            Ancestry::Halfling => (
+                Proficiency::Low,
+                Proficiency::Moderate,
+                Proficiency::High,
+                Proficiency::Low,
+            ),
+        };
+
+        stats.max_hp = new_hp(hp);
+        stats.movement_speed = new_speed(speed);
+        stats.initiative = new_initiative(initiative);
+        stats.inventory_slots = new_inventory(inventory);
+    }
+}
+
+impl Class {
+    pub fn all() -> Vec<Class> {
+        vec![Class::Fighter, Class::Archer, Class::Mage]
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Class::Fighter => "Heavily armored, strikes hard in melee",
+            Class::Archer => "Picks off enemies from a distance",
+            Class::Mage => "Wields magic, but is easily hurt",
+        }
+    }
+
+    fn apply(&self, stats: &mut Statistics) {
+        match self {
+            Class::Fighter => {
+                stats.armor_class = new_defense(Proficiency::High);
+                stats.fortitude_dc = new_defense(Proficiency::High);
+                stats.reflex_dc = new_defense(Proficiency::Moderate);
+                stats.will_dc = new_defense(Proficiency::Low);
+
+                stats.melee_attack = new_attack(Proficiency::High);
+                stats.ranged_attack = new_attack(Proficiency::Moderate);
+                stats.magic_attack = new_attack(Proficiency::Terrible);
+            }
+            Class::Archer => {
+                stats.armor_class = new_defense(Proficiency::Moderate);
+                stats.fortitude_dc = new_defense(Proficiency::Moderate);
+                stats.reflex_dc = new_defense(Proficiency::High);
+                stats.will_dc = new_defense(Proficiency::Moderate);
+
+                stats.melee_attack = new_attack(Proficiency::Low);
+                stats.ranged_attack = new_attack(Proficiency::High);
+                stats.magic_attack = new_attack(Proficiency::Low);
+            }
+            Class::Mage => {
+                stats.armor_class = new_defense(Proficiency::Low);
+                stats.fortitude_dc = new_defense(Proficiency::Low);
+                stats.reflex_dc = new_defense(Proficiency::Moderate);
+                stats.will_dc = new_defense(Proficiency::High);
+
+                stats.melee_attack = new_attack(Proficiency::Terrible);
+                stats.ranged_attack = new_attack(Proficiency::Low);
+                stats.magic_attack = new_attack(Proficiency::High);
+            }
+        }
+    }
+
+    // Names of the items in the item catalog the class starts with
+    pub fn get_starting_items(&self) -> &'static [&'static str] {
+        match self {
+            Class::Fighter => &["Longsword", "Wooden shield", "Dagger", "Healing potion"],
+            Class::Archer => &["Shortbow", "Dagger", "Leather armor", "Healing potion"],
+            Class::Mage => &["Staff of sparks", "Dagger", "Healing potion"],
+        }
+    }
+}
+
+impl fmt::Display for Ancestry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Ancestry::Human => "Human",
+            Ancestry::Elf => "Elf",
+            Ancestry::Dwarf => "Dwarf",
+            Ancestry::Halfling => "Halfling",
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+impl fmt::Display for Class {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Class::Fighter => "Fighter",
+            Class::Archer => "Archer",
+            Class::Mage => "Mage",
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+// Returns the new character and the items in its inventory. The items
+// still have to be added to the layer the character is placed on.
+pub fn create_character(
+    name: String,
+    options: CharacterOptions,
+    position: GridSquare,
+    catalog: &ItemCatalog,
+) -> (Creature, Vec<(Item, Effect)>) {
+    let mut stats = Statistics::get_default();
+    options.ancestry.apply(&mut stats);
+    options.class.apply(&mut stats);
+
+    let mut creature = Creature::new(name, position, stats);
+
+    let level = creature.stats.level;
+    let mut items: Vec<(Item, Effect)> = options
+        .class
+        .get_starting_items()
+        .iter()
+        .map(|name| {
+            catalog
+                .create_item(name, level, Rarity::Common)
+                .expect("starting items should exist in the item catalog")
+        })
+        .collect();
+
+    // Items that do not fit in the inventory are left behind
+    items.truncate(creature.inventory.len());
+
+    for (slot, (item, _)) in items.iter().enumerate() {
+        creature.inventory.replace_item(slot, item.get_id());
+
+        if let ItemKind::Passive(params) = &item.kind {
+            creature.equip_item(item.get_id(), params);
+        }
+    }
+
+    (creature, items)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stat::Stat;
+
+    const CATALOG: &str = include_str!("../../data/items.ron");
+
+    fn create(ancestry: Ancestry, class: Class) -> (Creature, Vec<(Item, Effect)>) {
+        let catalog = ItemCatalog::parse(CATALOG).unwrap();
+        let options = CharacterOptions { ancestry, class };
+        let position = GridSquare { y: 0, x: 0 };
+
+        create_character(String::from("hero"), options, position, &catalog)
+    }
+
+    #[test]
+    fn test_proficiencies() {
+        let value = |stat: &Stat, c: &Creature| stat.get_value(c.stats.level);
+
+        let (dwarf, _) = create(Ancestry::Dwarf, Class::Fighter);
+        let (elf, _) = create(Ancestry::Elf, Class::Fighter);
+        assert!(value(&dwarf.stats.max_hp, &dwarf) > value(&elf.stats.max_hp, &elf));
+        assert!(
+            value(&elf.stats.movement_speed, &elf) > value(&dwarf.stats.movement_speed, &dwarf)
+        );
+
+        let (fighter, _) = create(Ancestry::Human, Class::Fighter);
+        let (mage, _) = create(Ancestry::Human, Class::Mage);
+        let fighter_melee = value(&fighter.stats.melee_attack, &fighter);
+        assert!(fighter_melee > value(&mage.stats.melee_attack, &mage));
+        let mage_magic = value(&mage.stats.magic_attack, &mage);
+        assert!(mage_magic > value(&fighter.stats.magic_attack, &fighter));
+    }
+
+    #[test]
+    fn test_starting_inventory() {
+        for ancestry in Ancestry::all() {
+            for class in Class::all() {
+                let (creature, items) = create(ancestry, class);
+
+                let slots = creature
+                    .stats
+                    .inventory_slots
+                    .get_value(creature.stats.level) as usize;
+                assert_eq!(creature.inventory.len(), slots);
+                assert_eq!(items.len(), class.get_starting_items().len().min(slots));
+
+                for (slot, (item, _)) in items.iter().enumerate() {
+                    assert_eq!(item.name, class.get_starting_items()[slot]);
+                    assert_eq!(creature.inventory.get_item(slot), Some(item.get_id()));
+
+                    let is_passive = matches!(item.kind, ItemKind::Passive(_));
+                    assert_eq!(creature.equipment.is_equipped(item.get_id()), is_passive);
+                }
+            }
+        }
+
+        // Halflings only have room for the first two items
+        let (halfling, items) = create(Ancestry::Halfling, Class::Fighter);
+        assert_eq!(halfling.inventory.len(), 2);
+        assert_eq!(items.len(), 2);
+
+        // The shield is equipped right away
+        let (fighter, _) = create(Ancestry::Human, Class::Fighter);
+        let armor_class = &fighter.stats.armor_class;
+        let level = fighter.stats.level;
+        assert_eq!(
+            armor_class.get_value(level),
+            armor_class.get_value_without_modifiers(level) + 1
+        );
+    }
+}
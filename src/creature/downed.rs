@@ -0,0 +1,177 @@
+use crate::check::{self, Outcome};
+use crate::constants::DEATH_THRESHOLD;
+use crate::creature::DownedState;
+use crate::info_message::MessageType;
+use crate::world::Layer;
+
+// Flat check that a dying creature makes at the start of its turn
+const RECOVERY_DC: i32 = 10;
+const STABILIZE_DC: i32 = 15;
+
+fn is_player_controlled(c_id: u128, layer: &Layer) -> bool {
+    layer
+        .creature_ai
+        .get(&c_id)
+        .is_some_and(|ai| ai.is_player_controlled())
+}
+
+// Player characters that just ran out of HP fall unconscious instead of
+// dying. Returns a message for each of them.
+pub fn update_downed(layer: &mut Layer) -> Vec<MessageType> {
+    let mut messages: Vec<MessageType> = Vec::new();
+
+    let fallen: Vec<u128> = layer
+        .creatures
+        .iter()
+        .filter(|(_, c)| !c.is_alive() && c.downed.is_none())
+        .map(|(c_id, _)| *c_id)
+        .filter(|c_id| is_player_controlled(*c_id, layer))
+        .collect();
+
+    for c_id in fallen {
+        let creature = layer.creatures.get_mut(&c_id).unwrap();
+        creature.downed = Some(DownedState::Dying(1));
+        messages.push(MessageType::Info(format!(
+            "{} falls unconscious",
+            creature.name
+        )));
+    }
+
+    messages
+}
+
+fn set_dying(value: i32, name: &str) -> (DownedState, String) {
+    if value <= 0 {
+        (DownedState::Stable, format!("{} is stable", name))
+    } else if value >= DEATH_THRESHOLD {
+        (DownedState::Dead, format!("{} dies", name))
+    } else {
+        (
+            DownedState::Dying(value),
+            format!("{} is dying ({}/{})", name, value, DEATH_THRESHOLD),
+        )
+    }
+}
+
+// The DC gets harder the closer the creature is to death
+pub fn recovery_check(c_id: u128, layer: &mut Layer) -> MessageType {
+    let creature = layer
+        .creatures
+        .get_mut(&c_id)
+        .expect("creature should exist");
+    let dying = match creature.downed {
+        Some(DownedState::Dying(value)) => value,
+        _ => return MessageType::Error(String::from("Creature is not dying")),
+    };
+
+    let check = check::skill_check(0, RECOVERY_DC + dying);
+    let change = match check.outcome {
+        Outcome::CriticalSuccess => -2,
+        Outcome::Success => -1,
+        Outcome::Failure => 1,
+        Outcome::CriticalFailure => 2,
+    };
+
+    let (state, result) = set_dying(dying + change, &creature.name);
+    creature.downed = Some(state);
+
+    MessageType::Info(format!(
+        "{} makes a recovery check ({}). {}",
+        creature.name,
+        check::describe(&check),
+        result
+    ))
+}
+
+// Will is the closest thing to a medicine skill. A critical success gets
+// the target back up, and a critical failure makes things worse.
+pub fn stabilize(helper_id: u128, target_id: u128, layer: &mut Layer) -> MessageType {
+    let helper = layer
+        .creatures
+        .get(&helper_id)
+        .expect("helper should exist");
+    let helper_name = helper.name.clone();
+    let modifier = helper.stats.will_dc.get_value(helper.stats.level) - 10;
+
+    let target = layer
+        .creatures
+        .get_mut(&target_id)
+        .expect("target should exist");
+    let dying = match target.downed {
+        Some(DownedState::Dying(value)) => value,
+        _ => return MessageType::Error(String::from("Target is not dying")),
+    };
+
+    let check = check::skill_check(modifier, STABILIZE_DC);
+    let summary = format!(
+        "{} tends to {} ({})",
+        helper_name,
+        target.name,
+        check::describe(&check)
+    );
+
+    let result = match check.outcome {
+        Outcome::CriticalSuccess => {
+            target.change_hp(1);
+            format!("{} wakes up", target.name)
+        }
+        Outcome::Success => {
+            target.downed = Some(DownedState::Stable);
+            format!("{} is stable", target.name)
+        }
+        Outcome::Failure => String::from("nothing changes"),
+        Outcome::CriticalFailure => {
+            let (state, result) = set_dying(dying + 1, &target.name);
+            target.downed = Some(state);
+            result
+        }
+    };
+
+    MessageType::Info(format!("{}. {}", summary, result))
+}
+
+// The game is lost when no player character is left standing
+pub fn is_party_defeated(layer: &Layer) -> bool {
+    !layer
+        .creatures
+        .iter()
+        .any(|(c_id, c)| c.is_alive() && is_player_controlled(*c_id, layer))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::{Behavior, AI};
+    use crate::creature::statistics::Statistics;
+    use crate::creature::Creature;
+    use crate::grid::GridSquare;
+
+    #[test]
+    fn test_downed_and_defeat() {
+        let mut layer = Layer::new(3, 3);
+        let creature = Creature::new(
+            String::from("player"),
+            GridSquare { y: 1, x: 1 },
+            Statistics::get_default(),
+        );
+        let c_id = creature.get_id();
+        let behavior = Behavior::PlayerControlled(0);
+        layer.add_creature(creature, AI::new(c_id, behavior, behavior));
+        assert!(!is_party_defeated(&layer));
+
+        let player = layer.creatures.get_mut(&c_id).unwrap();
+        let hp = player.stats.current_hp;
+        player.change_hp(-hp);
+        assert_eq!(update_downed(&mut layer).len(), 1);
+        assert!(layer.creatures[&c_id].is_downed());
+        assert!(is_party_defeated(&layer));
+
+        // Recovery checks always move the creature somewhere
+        recovery_check(c_id, &mut layer);
+        assert!(layer.creatures[&c_id].downed != Some(DownedState::Dying(1)));
+
+        layer.creatures.get_mut(&c_id).unwrap().change_hp(1);
+        assert!(layer.creatures[&c_id].downed.is_none());
+        assert!(!is_party_defeated(&layer));
+    }
+}
@@ -0,0 +1,158 @@
+use crate::constants;
+use crate::creature::bestiary::{Bestiary, CreatureDefinition};
+use crate::creature::experience;
+use rand::distributions::WeightedIndex;
+use rand::prelude::*;
+
+// Budgets are defined for a party of this size and adjusted for others
+const BASE_PARTY_SIZE: i32 = 4;
+// Larger groups would not fit in the rooms
+const MAX_GROUP_SIZE: usize = 8;
+// Creatures this far below the party give no experience, and creatures
+// further above would be hopeless to fight
+const MAX_LEVEL_DIFFERENCE: i32 = 4;
+
+#[derive(Clone, Copy)]
+pub enum Difficulty {
+    Low,
+    Moderate,
+    Severe,
+}
+
+impl Difficulty {
+    pub fn all() -> Vec<Difficulty> {
+        vec![Difficulty::Low, Difficulty::Moderate, Difficulty::Severe]
+    }
+
+    // Relative probability of generating an encounter of this difficulty
+    pub fn get_weight(&self) -> u32 {
+        match self {
+            Difficulty::Low => 10,
+            Difficulty::Moderate => 7,
+            Difficulty::Severe => 3,
+        }
+    }
+
+    // Experience budget for a party of the given size. Each character
+    // more or less than the base party size adjusts the budget.
+    pub fn get_budget(&self, party_size: i32) -> i32 {
+        let (base, per_character) = match self {
+            Difficulty::Low => (60, 15),
+            Difficulty::Moderate => (80, 20),
+            Difficulty::Severe => (120, 30),
+        };
+
+        (base + (party_size - BASE_PARTY_SIZE) * per_character).max(per_character)
+    }
+}
+
+// Level the party is expected to have reached by the time it gets to
+// the layer
+pub fn get_party_level(depth: i32) -> i32 {
+    (1 + 2 * depth).min(constants::MAX_LEVEL)
+}
+
+pub struct EncounterMember<'a> {
+    pub definition: &'a CreatureDefinition,
+    pub level: i32,
+}
+
+// Creatures cost as much of the budget as they would give experience
+// to a character of the party level.
+pub fn get_cost(party_level: i32, level: i32) -> i32 {
+    experience::get_kill_experience(party_level, level)
+}
+
+// Spends the budget on creatures that can spawn at the depth, picking
+// them by spawn weight until nothing affordable is left.
+pub fn build_encounter<'a, R: Rng>(
+    bestiary: &'a Bestiary,
+    depth: i32,
+    party_level: i32,
+    budget: i32,
+    rng: &mut R,
+) -> Vec<EncounterMember<'a>> {
+    let min_level = (party_level - MAX_LEVEL_DIFFERENCE).max(constants::MIN_LEVEL);
+    let max_level = party_level + MAX_LEVEL_DIFFERENCE;
+
+    let mut members: Vec<EncounterMember> = Vec::new();
+    let mut remaining = budget;
+
+    while members.len() < MAX_GROUP_SIZE {
+        let mut candidates: Vec<EncounterMember> = Vec::new();
+        let mut weights: Vec<u32> = Vec::new();
+
+        for definition in bestiary.get_all() {
+            let weight = definition.get_spawn_weight(depth);
+            if weight == 0 {
+                continue;
+            }
+
+            let (def_min, def_max) = definition.level_range;
+            for level in def_min.max(min_level)..=def_max.min(max_level) {
+                let cost = get_cost(party_level, level);
+                if cost > 0 && cost <= remaining {
+                    candidates.push(EncounterMember { definition, level });
+                    weights.push(weight);
+                }
+            }
+        }
+
+        let distribution = match WeightedIndex::new(&weights) {
+            Ok(d) => d,
+            Err(_) => break,
+        };
+
+        let member = candidates.swap_remove(distribution.sample(rng));
+        remaining -= get_cost(party_level, member.level);
+        members.push(member);
+    }
+
+    members
+}
+
+pub fn choose_difficulty<R: Rng>(rng: &mut R) -> Difficulty {
+    let difficulties = Difficulty::all();
+    let weights: Vec<u32> = difficulties.iter().map(|d| d.get_weight()).collect();
+    let distribution = WeightedIndex::new(&weights).unwrap();
+
+    difficulties[distribution.sample(rng)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::item::catalog::ItemCatalog;
+    use rand_chacha::ChaCha20Rng;
+
+    const BESTIARY: &str = include_str!("../../data/bestiary.ron");
+    const CATALOG: &str = include_str!("../../data/items.ron");
+
+    #[test]
+    fn test_encounters_stay_within_budget() {
+        let catalog = ItemCatalog::parse(CATALOG).unwrap();
+        let bestiary = Bestiary::parse(BESTIARY, &catalog).unwrap();
+        let mut rng = ChaCha20Rng::seed_from_u64(0);
+
+        assert!(Difficulty::Moderate.get_budget(6) > Difficulty::Moderate.get_budget(4));
+        assert!(Difficulty::Severe.get_budget(1) > 0);
+
+        for depth in 0..constants::WORLD_LAYERS {
+            let party_level = get_party_level(depth);
+            let budget = Difficulty::Moderate.get_budget(4);
+            let encounter = build_encounter(&bestiary, depth, party_level, budget, &mut rng);
+            assert!(!encounter.is_empty(), "empty encounter at depth {}", depth);
+
+            let cost: i32 = encounter
+                .iter()
+                .map(|m| get_cost(party_level, m.level))
+                .sum();
+            assert!(cost <= budget);
+
+            for member in encounter {
+                assert!(member.level >= constants::MIN_LEVEL);
+                assert!(member.definition.get_spawn_weight(depth) > 0);
+            }
+        }
+    }
+}
@@ -0,0 +1,95 @@
+use crate::creature::statistics::CreatureStat;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+
+#[derive(Clone, Copy, Deserialize, PartialEq, Serialize)]
+pub enum EquipmentSlot {
+    Armor,
+    Shield,
+    Head,
+    Feet,
+    Neck,
+    Ring,
+}
+
+impl EquipmentSlot {
+    // How many items can be worn in the slot at the same time
+    pub fn capacity(&self) -> usize {
+        match self {
+            EquipmentSlot::Ring => 2,
+            _ => 1,
+        }
+    }
+}
+
+impl fmt::Display for EquipmentSlot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            EquipmentSlot::Armor => "Armor",
+            EquipmentSlot::Shield => "Shield",
+            EquipmentSlot::Head => "Head",
+            EquipmentSlot::Feet => "Feet",
+            EquipmentSlot::Neck => "Neck",
+            EquipmentSlot::Ring => "Ring",
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Clone, Copy, Deserialize, Serialize)]
+pub enum StatModifier {
+    Additive(CreatureStat, i32),
+    Multiplicative(CreatureStat, f64),
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+pub struct EquipmentParams {
+    pub slot: EquipmentSlot,
+    pub modifiers: Vec<StatModifier>,
+}
+
+// Equipped items are still stored in the inventory. This only keeps
+// track of which of them are worn and in which slot.
+#[derive(Clone, Default, Deserialize, Serialize)]
+pub struct Equipment {
+    items: HashMap<u128, EquipmentSlot>,
+}
+
+impl Equipment {
+    pub fn new() -> Self {
+        Self {
+            items: HashMap::new(),
+        }
+    }
+
+    pub fn is_equipped(&self, item_id: u128) -> bool {
+        self.items.contains_key(&item_id)
+    }
+
+    pub fn get_equipped_in(&self, slot: EquipmentSlot) -> Vec<u128> {
+        let mut items: Vec<u128> = Vec::new();
+
+        for (i_id, item_slot) in &self.items {
+            if *item_slot == slot {
+                items.push(*i_id);
+            }
+        }
+
+        items
+    }
+
+    pub fn is_full(&self, slot: EquipmentSlot) -> bool {
+        self.get_equipped_in(slot).len() >= slot.capacity()
+    }
+
+    pub fn equip(&mut self, item_id: u128, slot: EquipmentSlot) {
+        self.items.insert(item_id, slot);
+    }
+
+    // Returns true if the item was equipped.
+    pub fn unequip(&mut self, item_id: u128) -> bool {
+        self.items.remove(&item_id).is_some()
+    }
+}
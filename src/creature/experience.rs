@@ -0,0 +1,81 @@
+use crate::constants;
+use crate::info_message::{ExperienceMessage, MessageType};
+use crate::world::Layer;
+
+// Experience for defeating a creature depends on how much higher or lower
+// its level is compared to the creature that defeated it.
+pub fn get_kill_experience(killer_level: i32, victim_level: i32) -> i32 {
+    match victim_level - killer_level {
+        i32::MIN..=-5 => 0,
+        -4 => 10,
+        -3 => 15,
+        -2 => 20,
+        -1 => 30,
+        0 => 40,
+        1 => 60,
+        2 => 80,
+        3 => 120,
+        4..=i32::MAX => 160,
+    }
+}
+
+// Only player characters gain experience, so that monsters do not suddenly
+// grow stronger in the middle of a fight.
+pub fn award_kill_experience(
+    killer_id: u128,
+    victim_id: u128,
+    layer: &mut Layer,
+) -> Option<MessageType> {
+    let killer_ai = layer
+        .creature_ai
+        .get(&killer_id)
+        .expect("every creature should have an ai component");
+
+    if !killer_ai.is_player_controlled() || killer_id == victim_id {
+        return None;
+    }
+
+    let victim_level = layer.creatures.get(&victim_id).unwrap().stats.level;
+    let killer = layer.creatures.get_mut(&killer_id).unwrap();
+
+    // There is nothing left to gain
+    if killer.stats.level >= constants::MAX_LEVEL {
+        return None;
+    }
+
+    let experience = get_kill_experience(killer.stats.level, victim_level);
+    if experience == 0 {
+        return None;
+    }
+
+    let levels_gained = killer.gain_experience(experience);
+    let new_level = if levels_gained > 0 {
+        Some(killer.stats.level)
+    } else {
+        None
+    };
+
+    let message = ExperienceMessage {
+        creature: killer_id,
+        defeated: victim_id,
+        experience,
+        new_level,
+    };
+
+    Some(MessageType::Experience(message))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kill_experience() {
+        assert_eq!(get_kill_experience(5, 5), 40);
+        assert_eq!(get_kill_experience(5, 6), 60);
+        assert_eq!(get_kill_experience(5, 4), 30);
+        assert_eq!(get_kill_experience(5, 20), 160);
+        assert_eq!(get_kill_experience(10, 6), 10);
+        assert_eq!(get_kill_experience(10, 5), 0);
+    }
+}
@@ -41,4 +41,16 @@ impl Inventory {
 
         old_item
     }
+
+    pub fn swap_items(&mut self, first: usize, second: usize) {
+        self.items.swap(first, second);
+    }
+
+    pub fn find_item(&self, item_id: u128) -> Option<usize> {
+        self.items.iter().position(|x| *x == Some(item_id))
+    }
+
+    pub fn find_empty_slot(&self) -> Option<usize> {
+        self.items.iter().position(|x| x.is_none())
+    }
 }
@@ -1,6 +1,7 @@
 use crate::ai::AI;
 use crate::creature::Creature;
 use crate::grid::{gridalgos, Grid, GridSquare};
+use crate::world::spatial_index::SpatialIndex;
 use std::collections::HashMap;
 
 #[derive(PartialEq)]
@@ -32,20 +33,21 @@ impl Perception {
 
     pub fn seek(
         &self,
-        _grid: &Grid,
+        grid: &Grid,
         creatures: &HashMap<u128, Creature>,
+        positions: &SpatialIndex,
         cur_round: i64,
     ) -> Vec<Observation> {
         let mut observations: Vec<Observation> = Vec::new();
         let owner_pos = creatures.get(&self.owner_id).unwrap().get_position();
 
-        for (id, creature) in creatures {
-            let pos: GridSquare = creature.get_position();
+        // todo: get sense properties from creature stats
+        let sensing_distance = 5;
+
+        for id in &positions.get_in_radius(owner_pos, sensing_distance) {
+            let pos: GridSquare = creatures.get(id).unwrap().get_position();
 
-            // todo: get sense properties from creature stats
-            // todo: make obstacles block senses
-            let sensing_distance = 5;
-            if gridalgos::distance(owner_pos, pos) <= sensing_distance {
+            if gridalgos::line_of_sight(grid, owner_pos, pos) {
                 println!(
                     "creature {} noticed creature {} while seeking",
                     self.owner_id, *id
@@ -67,13 +69,14 @@ impl Perception {
         &mut self,
         grid: &Grid,
         creatures: &HashMap<u128, Creature>,
+        positions: &SpatialIndex,
         cur_round: i64,
     ) {
         let obs_max_lifetime_rounds = 3;
         self.observations
             .retain(|x| cur_round - x.round <= obs_max_lifetime_rounds);
 
-        let mut new_obs = self.seek(grid, creatures, cur_round);
+        let mut new_obs = self.seek(grid, creatures, positions, cur_round);
         self.observations.append(&mut new_obs);
         // todo: notify nearby creatures of the observation
     }
@@ -82,11 +85,12 @@ impl Perception {
         creature_ai: &mut HashMap<u128, AI>,
         grid: &Grid,
         creatures: &HashMap<u128, Creature>,
+        positions: &SpatialIndex,
         cur_round: i64,
     ) {
         for (_, c_ai) in creature_ai {
             c_ai.perception
-                .update_observations(grid, creatures, cur_round);
+                .update_observations(grid, creatures, positions, cur_round);
         }
     }
 
@@ -0,0 +1,159 @@
+use crate::check::Circumstance;
+use crate::grid::gridalgos;
+use crate::grid::GridSquare;
+use crate::world::Layer;
+
+// Player characters and non-player creatures are enemies of each other.
+pub fn are_hostile(a: u128, b: u128, layer: &Layer) -> bool {
+    let is_player = |c_id: u128| {
+        layer
+            .creature_ai
+            .get(&c_id)
+            .is_some_and(|ai| ai.is_player_controlled())
+    };
+
+    is_player(a) != is_player(b)
+}
+
+// A creature is flanked when two of its enemies stand next to it on
+// opposite sides.
+pub fn is_flanked(target_id: u128, layer: &Layer) -> bool {
+    let target = layer
+        .creatures
+        .get(&target_id)
+        .expect("target should exist");
+    let position = target.get_position();
+
+    let enemy_at = |square: GridSquare| {
+        layer
+            .get_living_creatures_at(square)
+            .into_iter()
+            .any(|c_id| are_hostile(c_id, target_id, layer))
+    };
+
+    gridalgos::get_neighbors(position).into_iter().any(|sq| {
+        let opposite = GridSquare {
+            y: 2 * position.y - sq.y,
+            x: 2 * position.x - sq.x,
+        };
+
+        enemy_at(sq) && enemy_at(opposite)
+    })
+}
+
+// Walls in the way give standard cover and other creatures lesser cover.
+// The origin is where the attack comes from, which is not always the
+// attacker's own square.
+pub fn get_cover(
+    origin: GridSquare,
+    attacker_id: u128,
+    target_id: u128,
+    layer: &Layer,
+) -> Option<Circumstance> {
+    let target = layer
+        .creatures
+        .get(&target_id)
+        .expect("target should exist");
+    let line = gridalgos::line(origin, target.get_position());
+    if line.len() <= 2 {
+        return None;
+    }
+
+    let between = &line[1..line.len() - 1];
+
+    if between.iter().any(|sq| !layer.grid.free_square(*sq)) {
+        return Some(Circumstance::StandardCover);
+    }
+
+    let blocked_by_creature = between.iter().any(|sq| {
+        layer
+            .get_living_creatures_at(*sq)
+            .into_iter()
+            .any(|c_id| c_id != attacker_id && c_id != target_id)
+    });
+
+    if blocked_by_creature {
+        return Some(Circumstance::LesserCover);
+    }
+
+    None
+}
+
+// Circumstances of an attack against the target
+pub fn get_circumstances(
+    origin: GridSquare,
+    attacker_id: u128,
+    target_id: u128,
+    layer: &Layer,
+) -> Vec<Circumstance> {
+    let mut circumstances: Vec<Circumstance> = Vec::new();
+
+    if is_flanked(target_id, layer) {
+        circumstances.push(Circumstance::Flanked);
+    }
+
+    if let Some(cover) = get_cover(origin, attacker_id, target_id, layer) {
+        circumstances.push(cover);
+    }
+
+    circumstances
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::{Behavior, AI};
+    use crate::creature::statistics::Statistics;
+    use crate::creature::Creature;
+    use crate::grid::Tile;
+
+    fn add_creature(position: GridSquare, behavior: Behavior, layer: &mut Layer) -> u128 {
+        let creature = Creature::new(
+            String::from("creature"),
+            position,
+            Statistics::get_default(),
+        );
+        let c_id = creature.get_id();
+        let c_ai = AI::new(c_id, behavior, behavior);
+        layer.add_creature(creature, c_ai);
+
+        c_id
+    }
+
+    #[test]
+    fn test_flanking_and_cover() {
+        // .....
+        // A#MP.
+        // .....
+        let mut layer = Layer::new(3, 5);
+        let wall = GridSquare { y: 1, x: 1 };
+        layer.grid.set_tile(wall, Tile::Wall);
+
+        let monster = add_creature(GridSquare { y: 1, x: 2 }, Behavior::Inactive, &mut layer);
+        let player = add_creature(
+            GridSquare { y: 1, x: 3 },
+            Behavior::PlayerControlled(0),
+            &mut layer,
+        );
+        assert!(!is_flanked(monster, &layer));
+
+        let archer_pos = GridSquare { y: 1, x: 0 };
+        let archer = add_creature(archer_pos, Behavior::PlayerControlled(1), &mut layer);
+        assert!(matches!(
+            get_cover(archer_pos, archer, monster, &layer),
+            Some(Circumstance::StandardCover)
+        ));
+
+        layer.grid.set_tile(wall, Tile::Empty);
+        assert!(get_cover(archer_pos, archer, monster, &layer).is_none());
+
+        // The flanker stands between the archer and the monster
+        add_creature(wall, Behavior::PlayerControlled(2), &mut layer);
+        assert!(is_flanked(monster, &layer));
+        assert!(!is_flanked(player, &layer));
+        assert!(matches!(
+            get_cover(archer_pos, archer, monster, &layer),
+            Some(Circumstance::LesserCover)
+        ));
+    }
+}
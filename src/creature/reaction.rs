@@ -0,0 +1,430 @@
+use crate::creature::action;
+use crate::creature::equipment::EquipmentSlot;
+use crate::creature::positioning::are_hostile;
+use crate::creature::Creature;
+use crate::grid::gridalgos;
+use crate::grid::GridSquare;
+use crate::info_message::{AttackMessage, MessageType};
+use crate::item::targeting::TargetKind;
+use crate::item::ItemKind;
+use crate::world::Layer;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+// Reactions are taken outside the creature's own turn, in response to
+// something another creature does.
+#[derive(Clone, Copy, Deserialize, Serialize)]
+pub enum Reaction {
+    AttackOfOpportunity(AttackOfOpportunityReaction),
+    ShieldBlock(ShieldBlockReaction),
+}
+
+// Strike an adjacent enemy that moves away
+#[derive(Clone, Copy, Deserialize, Serialize)]
+pub struct AttackOfOpportunityReaction {
+    pub reactor: u128,
+    pub target: u128,
+    pub item: u128,
+}
+
+// Absorb part of the damage of a hit with an equipped shield
+#[derive(Clone, Copy, Deserialize, Serialize)]
+pub struct ShieldBlockReaction {
+    pub reactor: u128,
+    pub attacker: u128,
+    pub shield: u128,
+    pub damage: i32,
+}
+
+impl Reaction {
+    pub fn get_reactor(&self) -> u128 {
+        match self {
+            Reaction::AttackOfOpportunity(r) => r.reactor,
+            Reaction::ShieldBlock(r) => r.reactor,
+        }
+    }
+
+    pub fn describe(&self, layer: &Layer) -> String {
+        let name = |c_id: u128| match layer.creatures.get(&c_id) {
+            Some(c) => c.name.clone(),
+            None => String::from("someone"),
+        };
+
+        match self {
+            Reaction::AttackOfOpportunity(r) => {
+                format!("Attack {} as it moves away?", name(r.target))
+            }
+            Reaction::ShieldBlock(r) => format!(
+                "Block {} damage from {} with your shield?",
+                get_blocked_damage(r.damage),
+                name(r.attacker)
+            ),
+        }
+    }
+}
+
+// Every creature can react once per round. Create a new tracker at the
+// start of each round.
+#[derive(Default)]
+pub struct ReactionTracker {
+    used: HashSet<u128>,
+}
+
+impl ReactionTracker {
+    pub fn new() -> Self {
+        Self {
+            used: HashSet::new(),
+        }
+    }
+
+    pub fn can_react(&self, creature_id: u128) -> bool {
+        !self.used.contains(&creature_id)
+    }
+
+    pub fn use_reaction(&mut self, creature_id: u128) {
+        self.used.insert(creature_id);
+    }
+}
+
+fn can_react(
+    reactor: &Creature,
+    trigger_creature: u128,
+    tracker: &ReactionTracker,
+    layer: &Layer,
+) -> bool {
+    reactor.is_alive()
+        && tracker.can_react(reactor.get_id())
+        && are_hostile(reactor.get_id(), trigger_creature, layer)
+}
+
+// A melee item is one that targets a single square within reach.
+fn find_melee_item(creature: &Creature, layer: &Layer) -> Option<u128> {
+    let inv = &creature.inventory;
+
+    (0..inv.len())
+        .filter_map(|slot| inv.get_item(slot))
+        .find(|i_id| {
+            let item = layer.items.get(i_id).expect("item should exist");
+            match item.kind {
+                ItemKind::Active(TargetKind::Square(p)) => p.range == 1 && !item.is_used_up(),
+                _ => false,
+            }
+        })
+}
+
+// Reactions triggered by a creature moving away from its current square.
+// Has to be called before the move is executed.
+pub fn get_move_reactions(
+    mover_id: u128,
+    destination: GridSquare,
+    tracker: &ReactionTracker,
+    layer: &Layer,
+) -> Vec<Reaction> {
+    let mover = layer.creatures.get(&mover_id).expect("mover should exist");
+    let source = mover.get_position();
+
+    if source == destination {
+        return Vec::new();
+    }
+
+    let mut reactions: Vec<Reaction> = Vec::new();
+
+    for c_id in layer.get_creatures_in_radius(source, 1) {
+        let creature = layer.creatures.get(&c_id).unwrap();
+
+        if !creature.is_alive()
+            || gridalgos::distance(creature.get_position(), source) != 1
+            || !can_react(creature, mover_id, tracker, layer)
+        {
+            continue;
+        }
+
+        if let Some(item) = find_melee_item(creature, layer) {
+            reactions.push(Reaction::AttackOfOpportunity(AttackOfOpportunityReaction {
+                reactor: c_id,
+                target: mover_id,
+                item,
+            }));
+        }
+    }
+
+    reactions
+}
+
+// Reactions triggered by the targets of an attack being hit
+pub fn get_hit_reactions(
+    attack: &AttackMessage,
+    tracker: &ReactionTracker,
+    layer: &Layer,
+) -> Vec<Reaction> {
+    let mut reactions: Vec<Reaction> = Vec::new();
+
+    for result in &attack.results {
+        if result.damage.total <= 0 {
+            continue;
+        }
+
+        let target = match layer.creatures.get(&result.target) {
+            Some(c) => c,
+            None => continue,
+        };
+
+        // HP is clamped at zero, so the damage of a killing blow can't
+        // be reliably undone. A shield is only raised by survivors.
+        if !target.is_alive()
+            || !tracker.can_react(target.get_id())
+            || !are_hostile(target.get_id(), attack.attacker, layer)
+        {
+            continue;
+        }
+
+        if let Some(shield) = target
+            .equipment
+            .get_equipped_in(EquipmentSlot::Shield)
+            .first()
+        {
+            reactions.push(Reaction::ShieldBlock(ShieldBlockReaction {
+                reactor: target.get_id(),
+                attacker: attack.attacker,
+                shield: *shield,
+                damage: result.damage.total,
+            }));
+        }
+    }
+
+    reactions
+}
+
+// A shield absorbs half of the damage, rounded up.
+pub fn get_blocked_damage(damage: i32) -> i32 {
+    (damage + 1) / 2
+}
+
+// Returns false if the trigger no longer applies, for example because
+// the reactor or the target died in an earlier reaction.
+pub fn is_valid(reaction: &Reaction, tracker: &ReactionTracker, layer: &Layer) -> bool {
+    let reactor = match layer.creatures.get(&reaction.get_reactor()) {
+        Some(c) => c,
+        None => return false,
+    };
+
+    if !tracker.can_react(reactor.get_id()) {
+        return false;
+    }
+
+    match reaction {
+        Reaction::AttackOfOpportunity(r) => {
+            reactor.is_alive() && layer.creatures.get(&r.target).is_some_and(|c| c.is_alive())
+        }
+        Reaction::ShieldBlock(_) => true,
+    }
+}
+
+pub fn execute(
+    reaction: &Reaction,
+    tracker: &mut ReactionTracker,
+    layer: &mut Layer,
+) -> MessageType {
+    tracker.use_reaction(reaction.get_reactor());
+
+    match reaction {
+        Reaction::AttackOfOpportunity(r) => {
+            let target = layer
+                .creatures
+                .get(&r.target)
+                .expect("target should be a valid creature")
+                .get_position();
+
+            let effect = layer
+                .effects
+                .get(&r.item)
+                .expect("an item should have an effect");
+
+            let slot = layer.creatures[&r.reactor]
+                .inventory
+                .find_item(r.item)
+                .expect("the reactor should carry the item");
+
+            let effect_result = (effect.apply)(r.item, r.reactor, target, layer);
+            action::use_charge(r.item, r.reactor, slot, layer);
+
+            if let Some(e) = effect_result.ongoing_effect {
+                layer.ongoing_effects.insert(e.get_id(), e);
+            }
+
+            effect_result.message
+        }
+        Reaction::ShieldBlock(r) => {
+            let blocked = get_blocked_damage(r.damage);
+
+            let reactor = layer
+                .creatures
+                .get_mut(&r.reactor)
+                .expect("reactor should be a valid creature");
+            reactor.change_hp(blocked);
+
+            let shield = layer.items.get(&r.shield).expect("item should exist");
+
+            MessageType::Info(format!(
+                "{} blocks {} damage with {}",
+                reactor.name, blocked, shield.name
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::{Behavior, AI};
+    use crate::check::{Check, Outcome};
+    use crate::creature::statistics::Statistics;
+    use crate::damage::{self, DamageType};
+    use crate::info_message::AttackResult;
+    use crate::item::catalog::ItemCatalog;
+    use crate::item::statistics::Rarity;
+
+    // Adds a creature carrying the item, equipped if it is passive
+    fn add_creature(
+        layer: &mut Layer,
+        position: GridSquare,
+        behavior: Behavior,
+        item_name: &str,
+    ) -> u128 {
+        let catalog = ItemCatalog::parse(include_str!("../../data/items.ron")).unwrap();
+        let mut creature = Creature::new(
+            String::from("creature"),
+            position,
+            Statistics::get_default(),
+        );
+        let c_id = creature.get_id();
+
+        let (item, effect) = catalog.create_item(item_name, 1, Rarity::Common).unwrap();
+        creature.inventory.replace_item(0, item.get_id());
+        if let ItemKind::Passive(params) = &item.kind {
+            creature.equip_item(item.get_id(), params);
+        }
+
+        layer.add_item(item, effect);
+        layer.add_creature(creature, AI::new(c_id, behavior, behavior));
+        c_id
+    }
+
+    #[test]
+    fn test_reaction_tracker() {
+        let mut tracker = ReactionTracker::new();
+        assert!(tracker.can_react(1));
+
+        tracker.use_reaction(1);
+        assert!(!tracker.can_react(1));
+        assert!(tracker.can_react(2));
+    }
+
+    #[test]
+    fn test_move_reactions() {
+        let mut layer = Layer::new(5, 5);
+        let player = Behavior::PlayerControlled(0);
+        let mover_id = add_creature(&mut layer, GridSquare { y: 2, x: 2 }, player, "Dagger");
+        let adjacent_id = add_creature(
+            &mut layer,
+            GridSquare { y: 2, x: 3 },
+            Behavior::Inactive,
+            "Dagger",
+        );
+        add_creature(
+            &mut layer,
+            GridSquare { y: 2, x: 4 },
+            Behavior::Inactive,
+            "Dagger",
+        );
+
+        // Only the adjacent enemy gets to attack
+        let destination = GridSquare { y: 2, x: 1 };
+        let mut tracker = ReactionTracker::new();
+        let reactions = get_move_reactions(mover_id, destination, &tracker, &layer);
+        assert_eq!(reactions.len(), 1);
+        assert_eq!(reactions[0].get_reactor(), adjacent_id);
+
+        tracker.use_reaction(adjacent_id);
+        assert!(get_move_reactions(mover_id, destination, &tracker, &layer).is_empty());
+
+        let tracker = ReactionTracker::new();
+        let adjacent = layer.creatures.get_mut(&adjacent_id).unwrap();
+        let hp = adjacent.stats.current_hp;
+        adjacent.change_hp(-hp);
+        assert!(get_move_reactions(mover_id, destination, &tracker, &layer).is_empty());
+    }
+
+    #[test]
+    fn test_attack_of_opportunity_uses_charges() {
+        let mut layer = Layer::new(5, 5);
+        let player = Behavior::PlayerControlled(0);
+        let mover_id = add_creature(&mut layer, GridSquare { y: 2, x: 2 }, player, "Dagger");
+        let reactor_id = add_creature(
+            &mut layer,
+            GridSquare { y: 2, x: 3 },
+            Behavior::Inactive,
+            "Dagger",
+        );
+
+        let item_id = layer.creatures[&reactor_id].inventory.get_item(0).unwrap();
+        layer.items.get_mut(&item_id).unwrap().charges = Some(1);
+
+        let destination = GridSquare { y: 2, x: 1 };
+        let mut tracker = ReactionTracker::new();
+        let reactions = get_move_reactions(mover_id, destination, &tracker, &layer);
+        assert_eq!(reactions.len(), 1);
+
+        execute(&reactions[0], &mut tracker, &mut layer);
+        assert!(layer.items[&item_id].is_used_up());
+        assert!(layer.creatures[&reactor_id].inventory.get_item(0).is_none());
+
+        let tracker = ReactionTracker::new();
+        assert!(get_move_reactions(mover_id, destination, &tracker, &layer).is_empty());
+    }
+
+    #[test]
+    fn test_shield_block() {
+        let mut layer = Layer::new(5, 5);
+        let player = Behavior::PlayerControlled(0);
+        let target_id = add_creature(
+            &mut layer,
+            GridSquare { y: 2, x: 2 },
+            player,
+            "Wooden shield",
+        );
+        let attacker_id = add_creature(
+            &mut layer,
+            GridSquare { y: 2, x: 3 },
+            Behavior::Inactive,
+            "Dagger",
+        );
+
+        let target = layer.creatures.get_mut(&target_id).unwrap();
+        let max_hp = target.stats.current_hp;
+        target.change_hp(-6);
+        let damage = damage::calculate(6, DamageType::Piercing, &target.stats);
+
+        let attack = AttackMessage {
+            attacker: attacker_id,
+            item: layer.creatures[&attacker_id].inventory.get_item(0).unwrap(),
+            results: vec![AttackResult {
+                target: target_id,
+                check: Check::new(15, 0, 10, Outcome::Success),
+                roll: None,
+                damage,
+            }],
+            broken_walls: 0,
+            broken_doors: 0,
+        };
+
+        let mut tracker = ReactionTracker::new();
+        let reactions = get_hit_reactions(&attack, &tracker, &layer);
+        assert_eq!(reactions.len(), 1);
+        assert!(is_valid(&reactions[0], &tracker, &layer));
+
+        execute(&reactions[0], &mut tracker, &mut layer);
+        assert_eq!(layer.creatures[&target_id].stats.current_hp, max_hp - 3);
+        assert!(get_hit_reactions(&attack, &tracker, &layer).is_empty());
+    }
+}
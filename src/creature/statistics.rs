@@ -1,10 +1,13 @@
+use crate::damage::DamageType;
 use crate::stat::{LevelScaling, Proficiency, Stat};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fmt;
 
 #[derive(Clone, Deserialize, Serialize)]
 pub struct Statistics {
     pub level: i32,
+    pub experience: i32,
 
     pub current_hp: i32,
     pub max_hp: Stat,
@@ -24,12 +27,84 @@ pub struct Statistics {
     pub n_actions: Stat,
 
     pub inventory_slots: Stat,
+
+    pub resistances: HashMap<DamageType, Stat>,
+    pub weaknesses: HashMap<DamageType, Stat>,
+    pub immunities: Vec<DamageType>,
+}
+
+// Stats that items and effects can modify. Inventory size is fixed when
+// the creature is created, so it is not included.
+#[derive(Clone, Copy, Deserialize, PartialEq, Serialize)]
+pub enum CreatureStat {
+    MaxHp,
+    ArmorClass,
+    FortitudeDc,
+    ReflexDc,
+    WillDc,
+    MeleeAttack,
+    RangedAttack,
+    MagicAttack,
+    MovementSpeed,
+    Initiative,
+    NActions,
+}
+
+impl CreatureStat {
+    pub fn all() -> Vec<CreatureStat> {
+        vec![
+            CreatureStat::MaxHp,
+            CreatureStat::ArmorClass,
+            CreatureStat::FortitudeDc,
+            CreatureStat::ReflexDc,
+            CreatureStat::WillDc,
+            CreatureStat::MeleeAttack,
+            CreatureStat::RangedAttack,
+            CreatureStat::MagicAttack,
+            CreatureStat::MovementSpeed,
+            CreatureStat::Initiative,
+            CreatureStat::NActions,
+        ]
+    }
 }
 
 impl Statistics {
+    pub fn get_stat(&self, stat: CreatureStat) -> &Stat {
+        match stat {
+            CreatureStat::MaxHp => &self.max_hp,
+            CreatureStat::ArmorClass => &self.armor_class,
+            CreatureStat::FortitudeDc => &self.fortitude_dc,
+            CreatureStat::ReflexDc => &self.reflex_dc,
+            CreatureStat::WillDc => &self.will_dc,
+            CreatureStat::MeleeAttack => &self.melee_attack,
+            CreatureStat::RangedAttack => &self.ranged_attack,
+            CreatureStat::MagicAttack => &self.magic_attack,
+            CreatureStat::MovementSpeed => &self.movement_speed,
+            CreatureStat::Initiative => &self.initiative,
+            CreatureStat::NActions => &self.n_actions,
+        }
+    }
+
+    pub fn get_stat_mut(&mut self, stat: CreatureStat) -> &mut Stat {
+        match stat {
+            CreatureStat::MaxHp => &mut self.max_hp,
+            CreatureStat::ArmorClass => &mut self.armor_class,
+            CreatureStat::FortitudeDc => &mut self.fortitude_dc,
+            CreatureStat::ReflexDc => &mut self.reflex_dc,
+            CreatureStat::WillDc => &mut self.will_dc,
+            CreatureStat::MeleeAttack => &mut self.melee_attack,
+            CreatureStat::RangedAttack => &mut self.ranged_attack,
+            CreatureStat::MagicAttack => &mut self.magic_attack,
+            CreatureStat::MovementSpeed => &mut self.movement_speed,
+            CreatureStat::Initiative => &mut self.initiative,
+            CreatureStat::NActions => &mut self.n_actions,
+        }
+    }
+
     pub fn get_default() -> Self {
         Self {
             level: 1,
+            experience: 0,
 
             current_hp: 0,
             max_hp: new_hp(Proficiency::Moderate),
@@ -49,6 +124,10 @@ impl Statistics {
             inventory_slots: new_inventory(Proficiency::Moderate),
 
             n_actions: new_actions(Proficiency::Moderate),
+
+            resistances: HashMap::new(),
+            weaknesses: HashMap::new(),
+            immunities: Vec::new(),
         }
     }
 }
@@ -57,7 +136,7 @@ impl fmt::Display for Statistics {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let level = self.level;
 
-        write!(f, "Level: {}\n", level)?;
+        write!(f, "Level: {}, XP: {}\n", level, self.experience)?;
 
         write!(
             f,
@@ -87,6 +166,25 @@ impl fmt::Display for Statistics {
             self.inventory_slots.get_value(level)
         )?;
 
+        let describe = |values: &HashMap<DamageType, Stat>| {
+            let described: Vec<String> = values
+                .iter()
+                .map(|(t, v)| format!("{} {}", t, v.get_value(level)))
+                .collect();
+            described.join(", ")
+        };
+
+        if !self.resistances.is_empty() {
+            writeln!(f, "[Resistances] {}", describe(&self.resistances))?;
+        }
+        if !self.weaknesses.is_empty() {
+            writeln!(f, "[Weaknesses] {}", describe(&self.weaknesses))?;
+        }
+        if !self.immunities.is_empty() {
+            let immunities: Vec<String> = self.immunities.iter().map(|t| t.to_string()).collect();
+            writeln!(f, "[Immunities] {}", immunities.join(", "))?;
+        }
+
         Ok(())
     }
 }
@@ -142,6 +240,26 @@ pub fn new_hp(prof: Proficiency) -> Stat {
     Stat::new(raw_hp, LevelScaling::Exponential(increase_per_level))
 }
 
+// Weaknesses use the same values. Both grow at the same rate as damage,
+// so they stay equally relevant at every level.
+pub fn new_resistance(prof: Proficiency) -> Stat {
+    let raw_resistance: i32 = match prof {
+        Proficiency::Extreme => 5,
+        Proficiency::High => 4,
+        Proficiency::Moderate => 3,
+        Proficiency::Low => 2,
+        Proficiency::Terrible => 1,
+    };
+
+    let double_after_levels = 2.0;
+    let increase_per_level: f64 = f64::powf(2.0, 1.0 / double_after_levels);
+
+    Stat::new(
+        raw_resistance,
+        LevelScaling::Exponential(increase_per_level),
+    )
+}
+
 pub fn new_initiative(prof: Proficiency) -> Stat {
     let raw_init = match prof {
         Proficiency::Extreme => 4,
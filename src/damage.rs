@@ -0,0 +1,133 @@
+use crate::creature::statistics::Statistics;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum DamageType {
+    Bludgeoning,
+    Piercing,
+    Slashing,
+    Fire,
+    Cold,
+    Electricity,
+    Poison,
+}
+
+impl fmt::Display for DamageType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            DamageType::Bludgeoning => "bludgeoning",
+            DamageType::Piercing => "piercing",
+            DamageType::Slashing => "slashing",
+            DamageType::Fire => "fire",
+            DamageType::Cold => "cold",
+            DamageType::Electricity => "electricity",
+            DamageType::Poison => "poison",
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+// How the target's defenses changed the damage
+#[derive(Clone, Copy, Deserialize, PartialEq, Serialize)]
+pub enum DamageAdjustment {
+    None,
+    Resistance(i32),
+    Weakness(i32),
+    Immunity,
+}
+
+#[derive(Clone, Copy, Deserialize, Serialize)]
+pub struct Damage {
+    pub base: i32,
+    pub damage_type: DamageType,
+    pub adjustment: DamageAdjustment,
+    pub total: i32,
+}
+
+// Immunities ignore the damage completely and resistances reduce it.
+// Weaknesses only come into play if the attack does damage in the first
+// place.
+pub fn calculate(base: i32, damage_type: DamageType, stats: &Statistics) -> Damage {
+    let adjustment = if base <= 0 {
+        DamageAdjustment::None
+    } else if stats.immunities.contains(&damage_type) {
+        DamageAdjustment::Immunity
+    } else if let Some(resistance) = stats.resistances.get(&damage_type) {
+        DamageAdjustment::Resistance(resistance.get_value(stats.level))
+    } else if let Some(weakness) = stats.weaknesses.get(&damage_type) {
+        DamageAdjustment::Weakness(weakness.get_value(stats.level))
+    } else {
+        DamageAdjustment::None
+    };
+
+    let total = match adjustment {
+        DamageAdjustment::None => base,
+        DamageAdjustment::Resistance(r) => (base - r).max(0),
+        DamageAdjustment::Weakness(w) => base + w,
+        DamageAdjustment::Immunity => 0,
+    };
+
+    Damage {
+        base,
+        damage_type,
+        adjustment,
+        total,
+    }
+}
+
+impl Damage {
+    // Explains how the total was reached, if the base damage was adjusted
+    pub fn describe_adjustment(&self) -> Option<String> {
+        match self.adjustment {
+            DamageAdjustment::None => None,
+            DamageAdjustment::Resistance(r) => Some(format!("{} - {} resistance", self.base, r)),
+            DamageAdjustment::Weakness(w) => Some(format!("{} + {} weakness", self.base, w)),
+            DamageAdjustment::Immunity => Some(String::from("immune")),
+        }
+    }
+}
+
+impl fmt::Display for Damage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} damage", self.total, self.damage_type)?;
+
+        if let Some(adjustment) = self.describe_adjustment() {
+            write!(f, " ({})", adjustment)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::creature::statistics::{self as creature_statistics, Statistics};
+    use crate::stat::Proficiency;
+
+    #[test]
+    fn test_calculate() {
+        let mut stats = Statistics::get_default();
+        stats.immunities.push(DamageType::Poison);
+        stats.resistances.insert(
+            DamageType::Fire,
+            creature_statistics::new_resistance(Proficiency::Moderate),
+        );
+        stats.weaknesses.insert(
+            DamageType::Cold,
+            creature_statistics::new_resistance(Proficiency::Moderate),
+        );
+        let amount = stats.resistances[&DamageType::Fire].get_value(stats.level);
+
+        assert_eq!(calculate(10, DamageType::Slashing, &stats).total, 10);
+        assert_eq!(calculate(10, DamageType::Poison, &stats).total, 0);
+        assert_eq!(calculate(10, DamageType::Fire, &stats).total, 10 - amount);
+        assert_eq!(calculate(1, DamageType::Fire, &stats).total, 0);
+        assert_eq!(calculate(10, DamageType::Cold, &stats).total, 10 + amount);
+
+        // Misses stay misses
+        assert_eq!(calculate(0, DamageType::Cold, &stats).total, 0);
+    }
+}
@@ -0,0 +1,35 @@
+use serde::de::DeserializeOwned;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+#[derive(Debug)]
+pub enum DataError {
+    Io(String),
+    Parse(String),
+    Invalid(String),
+}
+
+impl fmt::Display for DataError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DataError::Io(msg) => write!(f, "failed to read data file: {}", msg),
+            DataError::Parse(msg) => write!(f, "failed to parse data file: {}", msg),
+            DataError::Invalid(msg) => write!(f, "invalid data: {}", msg),
+        }
+    }
+}
+
+pub fn parse<T: DeserializeOwned>(data: &str) -> Result<T, DataError> {
+    ron::from_str(data).map_err(|e| DataError::Parse(e.to_string()))
+}
+
+pub fn load<T: DeserializeOwned>(path: &Path) -> Result<T, DataError> {
+    let data = fs::read_to_string(path)
+        .map_err(|e| DataError::Io(format!("{}: {}", path.display(), e)))?;
+
+    parse(&data).map_err(|e| match e {
+        DataError::Parse(msg) => DataError::Parse(format!("{}: {}", path.display(), msg)),
+        other => other,
+    })
+}
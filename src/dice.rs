@@ -0,0 +1,187 @@
+use crate::stat::LevelScaling;
+use rand::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+// Upper limits that keep typos in data files from creating absurd dice
+const MAX_COUNT: i32 = 100;
+const MAX_SIDES: i32 = 100;
+
+// Dice expression such as 2d6+3. Serialized in the same written form.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Dice {
+    pub count: i32,
+    pub sides: i32,
+    pub bonus: i32,
+}
+
+impl Dice {
+    pub fn new(count: i32, sides: i32, bonus: i32) -> Self {
+        Self {
+            count,
+            sides,
+            bonus,
+        }
+    }
+
+    pub fn average(&self) -> f64 {
+        self.count as f64 * (self.sides + 1) as f64 / 2.0 + self.bonus as f64
+    }
+
+    // Rolls never go below zero, even with a negative bonus
+    pub fn roll<R: Rng>(&self, rng: &mut R) -> DiceRoll {
+        let rolls: Vec<i32> = (0..self.count)
+            .map(|_| rng.gen_range(1..=self.sides))
+            .collect();
+        let total = (rolls.iter().sum::<i32>() + self.bonus).max(0);
+
+        DiceRoll {
+            dice: *self,
+            rolls,
+            total,
+        }
+    }
+}
+
+impl FromStr for Dice {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid dice expression \"{}\"", s);
+        let expression: String = s.chars().filter(|c| !c.is_whitespace()).collect();
+
+        let (count, rest) = expression.split_once('d').ok_or_else(invalid)?;
+
+        // The count can be left out, as in d20
+        let count = match count {
+            "" => 1,
+            _ => count.parse::<i32>().map_err(|_| invalid())?,
+        };
+
+        let (sides, bonus) = match rest.find(['+', '-']) {
+            Some(i) => {
+                if rest[i + 1..].starts_with(['+', '-']) {
+                    return Err(invalid());
+                }
+                let bonus = rest[i + 1..].parse::<i32>().map_err(|_| invalid())?;
+                let sign = if rest[i..].starts_with('-') { -1 } else { 1 };
+                (&rest[..i], sign * bonus)
+            }
+            None => (rest, 0),
+        };
+        let sides = sides.parse::<i32>().map_err(|_| invalid())?;
+
+        if !(1..=MAX_COUNT).contains(&count) || !(1..=MAX_SIDES).contains(&sides) {
+            return Err(format!(
+                "dice expression \"{}\" must have 1 to {} dice with 1 to {} sides",
+                s, MAX_COUNT, MAX_SIDES
+            ));
+        }
+
+        Ok(Dice::new(count, sides, bonus))
+    }
+}
+
+impl TryFrom<String> for Dice {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<Dice> for String {
+    fn from(dice: Dice) -> Self {
+        dice.to_string()
+    }
+}
+
+impl fmt::Display for Dice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}d{}", self.count, self.sides)?;
+
+        if self.bonus != 0 {
+            write!(f, "{:+}", self.bonus)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+pub struct DiceRoll {
+    pub dice: Dice,
+    pub rolls: Vec<i32>,
+    pub total: i32,
+}
+
+impl fmt::Display for DiceRoll {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} = {}", self.dice, self.total)
+    }
+}
+
+// Dice that grow with level. The average follows the level scaling, and
+// the die size stays the same, so higher levels roll more dice.
+#[derive(Clone, Deserialize, Serialize)]
+pub struct ScaledDice {
+    dice: Dice,
+    level_scaling: LevelScaling,
+}
+
+impl ScaledDice {
+    pub fn new(dice: Dice, scaling: LevelScaling) -> Self {
+        Self {
+            dice,
+            level_scaling: scaling,
+        }
+    }
+
+    pub fn get_dice(&self, level: i32) -> Dice {
+        let average = self.dice.average();
+        let scaled_average = self.level_scaling.apply(average, level);
+        let die_average = (self.dice.sides + 1) as f64 / 2.0;
+
+        let count = (self.dice.count as f64 * scaled_average / average).round() as i32;
+        let count = count.clamp(1, MAX_COUNT);
+        let bonus = (scaled_average - count as f64 * die_average).round() as i32;
+
+        Dice::new(count, self.dice.sides, bonus)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!("2d6+3".parse(), Ok(Dice::new(2, 6, 3)));
+        assert_eq!("1d8".parse(), Ok(Dice::new(1, 8, 0)));
+        assert_eq!("d20 - 1".parse(), Ok(Dice::new(1, 20, -1)));
+        assert_eq!(Dice::new(3, 4, -2).to_string(), "3d4-2");
+
+        for invalid in ["", "6", "2d", "0d6", "2d6+", "2x6", "1d6+2+3", "1d6+-2"] {
+            assert!(invalid.parse::<Dice>().is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_roll_and_scale() {
+        let mut rng = rand::thread_rng();
+        let dice = Dice::new(2, 6, 3);
+
+        for _ in 0..100 {
+            let roll = dice.roll(&mut rng);
+            assert_eq!(roll.rolls.len(), 2);
+            assert!((5..=15).contains(&roll.total));
+        }
+
+        let scaled = ScaledDice::new(dice, LevelScaling::Exponential(2.0));
+        let scaled_dice = scaled.get_dice(-1);
+        assert_eq!(scaled_dice.sides, 6);
+        assert!((scaled_dice.average() - 2.0 * dice.average()).abs() <= 0.5);
+    }
+}
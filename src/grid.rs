@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fmt;
+use std::str::FromStr;
 
 pub mod gridalgos;
 
@@ -7,6 +9,126 @@ pub mod gridalgos;
 pub enum Tile {
     Empty,
     Wall,
+    // Undergrowth, mud and the like that slows movement
+    DifficultTerrain,
+    // Shallow enough to wade through
+    Water,
+    // Can't be walked over, but does not block anything else
+    Chasm,
+    Rubble,
+    Door(DoorState),
+    // Set into a wall. Pulling it unlocks the doors around it.
+    Lever(LeverState),
+    // Leads to the layer above or below
+    Stairs(StairsDirection),
+}
+
+#[derive(Clone, Copy, Deserialize, PartialEq, Serialize)]
+pub enum DoorState {
+    Open,
+    Closed,
+    // Only a lever can open a locked door
+    Locked,
+}
+
+#[derive(Clone, Copy, Deserialize, PartialEq, Serialize)]
+pub enum LeverState {
+    Up,
+    Pulled,
+}
+
+#[derive(Clone, Copy, Deserialize, PartialEq, Serialize)]
+pub enum StairsDirection {
+    Up,
+    Down,
+}
+
+impl Tile {
+    // Cost of stepping onto the tile, or None if creatures can't enter it.
+    // Diagonal steps cost the same extra on top of the usual diagonal cost.
+    pub fn movement_cost(&self) -> Option<i32> {
+        match self {
+            Tile::Empty | Tile::Door(DoorState::Open) | Tile::Stairs(_) => Some(1),
+            Tile::DifficultTerrain | Tile::Rubble => Some(2),
+            Tile::Water => Some(3),
+            Tile::Wall | Tile::Chasm | Tile::Door(_) | Tile::Lever(_) => None,
+        }
+    }
+
+    pub fn is_passable(&self) -> bool {
+        self.movement_cost().is_some()
+    }
+
+    // Solid tiles block sight and area effects in addition to movement.
+    pub fn is_solid(&self) -> bool {
+        match self {
+            Tile::Wall | Tile::Lever(_) => true,
+            Tile::Door(state) => *state != DoorState::Open,
+            _ => false,
+        }
+    }
+
+    pub fn is_interactable(&self) -> bool {
+        matches!(self, Tile::Door(_) | Tile::Lever(_))
+    }
+
+    // Damage the tile can take before it breaks, or None if it can't be
+    // broken at all
+    pub fn hit_points(&self) -> Option<i32> {
+        match self {
+            Tile::Wall => Some(30),
+            Tile::Door(_) => Some(15),
+            _ => None,
+        }
+    }
+
+    // What is left after the tile breaks
+    pub fn broken(&self) -> Tile {
+        match self {
+            Tile::Wall => Tile::Rubble,
+            _ => Tile::Empty,
+        }
+    }
+
+    // Character of the tile in ASCII maps
+    pub fn to_char(&self) -> char {
+        match self {
+            Tile::Empty => '.',
+            Tile::Wall => '#',
+            Tile::DifficultTerrain => ',',
+            Tile::Water => '~',
+            Tile::Chasm => ' ',
+            Tile::Rubble => ':',
+            Tile::Door(DoorState::Open) => '/',
+            Tile::Door(DoorState::Closed) => '+',
+            Tile::Door(DoorState::Locked) => '=',
+            Tile::Lever(LeverState::Up) => '!',
+            Tile::Lever(LeverState::Pulled) => '|',
+            Tile::Stairs(StairsDirection::Up) => '<',
+            Tile::Stairs(StairsDirection::Down) => '>',
+        }
+    }
+
+    pub fn from_char(c: char) -> Option<Tile> {
+        let tile = match c {
+            '.' => Tile::Empty,
+            '#' => Tile::Wall,
+            ',' => Tile::DifficultTerrain,
+            '~' => Tile::Water,
+            ' ' => Tile::Chasm,
+            ':' => Tile::Rubble,
+            '/' => Tile::Door(DoorState::Open),
+            '+' => Tile::Door(DoorState::Closed),
+            '=' => Tile::Door(DoorState::Locked),
+            '!' => Tile::Lever(LeverState::Up),
+            '|' => Tile::Lever(LeverState::Pulled),
+            '<' => Tile::Stairs(StairsDirection::Up),
+            '>' => Tile::Stairs(StairsDirection::Down),
+            _ => return None,
+        };
+
+        Some(tile)
+    }
 }
 
 #[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
@@ -31,6 +153,8 @@ pub struct GridIntersection {
 #[derive(Clone, Deserialize, Serialize)]
 pub struct Grid {
     grid: Vec<Vec<Tile>>,
+    // Damage taken by tiles that haven't broken yet
+    damage: HashMap<GridSquare, i32>,
 }
 
 impl Grid {
@@ -41,6 +165,7 @@ impl Grid {
 
         Self {
             grid: vec![vec![Tile::Empty; width as usize]; height as usize],
+            damage: HashMap::new(),
         }
     }
 
@@ -56,18 +181,27 @@ impl Grid {
         square.y >= 0 && square.y < self.height() && square.x >= 0 && square.x < self.width()
     }
 
+    // Returns true if the square is not inside a wall.
     pub fn free_square(&self, square: GridSquare) -> bool {
         if !self.valid_square(square) {
             return false;
         }
 
-        if self.get_tile(square).unwrap() != Tile::Empty {
+        if self.get_tile(square).unwrap().is_solid() {
             return false;
         }
 
         return true;
     }
 
+    // Returns true if creatures can stand on the square.
+    pub fn passable_square(&self, square: GridSquare) -> bool {
+        match self.get_tile(square) {
+            Some(tile) => tile.is_passable(),
+            None => false,
+        }
+    }
+
     pub fn get_tile(&self, square: GridSquare) -> Option<Tile> {
         if !self.valid_square(square) {
             return None;
@@ -80,6 +214,51 @@ impl Grid {
             panic!("out of bounds write to grid square {}", square);
         }
         self.grid[square.y as usize][square.x as usize] = tile;
+        self.damage.remove(&square);
+    }
+
+    // The outermost squares keep creatures inside the grid, so they can't
+    // be broken.
+    pub fn destructible_square(&self, square: GridSquare) -> bool {
+        let on_edge = square.y == 0
+            || square.x == 0
+            || square.y == self.height() - 1
+            || square.x == self.width() - 1;
+
+        match self.get_tile(square) {
+            Some(tile) => !on_edge && tile.hit_points().is_some(),
+            None => false,
+        }
+    }
+
+    // Hit points the tile has left, or None if it can't be broken
+    pub fn get_tile_hp(&self, square: GridSquare) -> Option<i32> {
+        if !self.destructible_square(square) {
+            return None;
+        }
+
+        let max_hp = self.get_tile(square).unwrap().hit_points().unwrap();
+        let damage = self.damage.get(&square).copied().unwrap_or(0);
+
+        Some(max_hp - damage)
+    }
+
+    // Returns true if the tile broke
+    pub fn damage_tile(&mut self, square: GridSquare, amount: i32) -> bool {
+        let hp = match self.get_tile_hp(square) {
+            Some(hp) => hp,
+            None => panic!("grid square {} can't be damaged", square),
+        };
+
+        if amount < hp {
+            *self.damage.entry(square).or_insert(0) += amount;
+            return false;
+        }
+
+        let broken = self.get_tile(square).unwrap().broken();
+        self.set_tile(square, broken);
+
+        true
     }
 }
 
@@ -90,14 +269,55 @@ impl fmt::Display for Grid {
                 let square = GridSquare { y: i, x: j };
                 let tile = self.get_tile(square).unwrap();
 
-                match tile {
-                    Tile::Empty => write!(f, "."),
-                    _ => write!(f, "#"),
-                }?;
+                write!(f, "{}", tile.to_char())?;
             }
-            write!(f, "\n")?;
+            writeln!(f)?;
         }
 
         Ok(())
     }
 }
+
+// Reads the same format that Display writes. Empty lines before and
+// after the map are ignored, so maps can start on their own line.
+impl FromStr for Grid {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lines: Vec<&str> = s.lines().collect();
+        let first = lines.iter().position(|l| !l.is_empty());
+        let last = lines.iter().rposition(|l| !l.is_empty());
+
+        let lines = match (first, last) {
+            (Some(first), Some(last)) => &lines[first..=last],
+            _ => return Err(String::from("map is empty")),
+        };
+
+        let width = lines[0].chars().count();
+        let mut grid = Grid::new(lines.len() as i32, width as i32);
+
+        for (y, line) in lines.iter().enumerate() {
+            if line.chars().count() != width {
+                return Err(format!(
+                    "row {} is {} characters wide instead of {}",
+                    y,
+                    line.chars().count(),
+                    width
+                ));
+            }
+
+            for (x, c) in line.chars().enumerate() {
+                let tile = Tile::from_char(c)
+                    .ok_or_else(|| format!("unknown tile '{}' at row {}, column {}", c, y, x))?;
+
+                let square = GridSquare {
+                    y: y as i32,
+                    x: x as i32,
+                };
+                grid.set_tile(square, tile);
+            }
+        }
+
+        Ok(grid)
+    }
+}
@@ -1,7 +1,9 @@
-use crate::grid::GridSquare;
+use crate::grid::{Grid, GridSquare};
 use crate::world::Layer;
+use std::cell::RefCell;
 use std::cmp;
-use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::collections::BinaryHeap;
+use std::ops::Index;
 
 // Horizontal and vertical steps cost 1 unit each.
 // Diagonal steps cost 1 and 2 units alternately.
@@ -15,195 +17,429 @@ pub fn distance(a: GridSquare, b: GridSquare) -> i32 {
     mx + mi - (mi + 1) / 2
 }
 
+// In the order the neighbors are visited by the path searches
+const NEIGHBOR_OFFSETS: [(i32, i32); 8] = [
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+    (0, -1),
+    (0, 1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+];
+
 pub fn get_neighbors(square: GridSquare) -> Vec<GridSquare> {
-    let mut result: Vec<GridSquare> = Vec::new();
-    for dy in -1..2 {
-        for dx in -1..2 {
-            if dx == 0 && dy == 0 {
-                continue;
-            }
+    NEIGHBOR_OFFSETS
+        .iter()
+        .map(|(dy, dx)| GridSquare {
+            y: square.y + dy,
+            x: square.x + dx,
+        })
+        .collect()
+}
 
-            let neighbor = GridSquare {
-                y: square.y + dy,
-                x: square.x + dx,
-            };
+pub fn emanation(center: GridSquare, radius: i32) -> Vec<GridSquare> {
+    let mut result: Vec<GridSquare> = Vec::new();
 
-            result.push(neighbor);
+    for cy in (center.y - radius)..(center.y + radius + 1) {
+        for cx in (center.x - radius)..(center.x + radius + 1) {
+            let sq = GridSquare { y: cy, x: cx };
+            if distance(sq, center) <= radius {
+                result.push(sq);
+            }
         }
     }
 
+    result.sort_by_key(|k| distance(*k, center));
+
     result
 }
 
-fn is_diagonal_neighbor(a: GridSquare, b: GridSquare) -> bool {
+// Squares on the straight line from a to b, including both ends
+pub fn line(a: GridSquare, b: GridSquare) -> Vec<GridSquare> {
     let dy = (b.y - a.y).abs();
     let dx = (b.x - a.x).abs();
+    let sy = if a.y < b.y { 1 } else { -1 };
+    let sx = if a.x < b.x { 1 } else { -1 };
 
-    assert!(dy <= 1 && dx <= 1, "the squares are not neighbors");
+    let mut result: Vec<GridSquare> = Vec::new();
+    let mut cur = a;
+    let mut err = dx - dy;
+
+    loop {
+        result.push(cur);
+        if cur == b {
+            break;
+        }
 
-    (dy != 0) && (dx != 0)
+        let e2 = 2 * err;
+        if e2 > -dy {
+            err -= dy;
+            cur.x += sx;
+        }
+        if e2 < dx {
+            err += dx;
+            cur.y += sy;
+        }
+    }
+
+    result
 }
 
-pub fn emanation(center: GridSquare, radius: i32) -> Vec<GridSquare> {
-    let mut result: Vec<GridSquare> = Vec::new();
+// Solid tiles between the squares block the line of sight. The squares
+// themselves may be solid, so that walls and doors can be seen.
+pub fn line_of_sight(grid: &Grid, a: GridSquare, b: GridSquare) -> bool {
+    let squares = line(a, b);
+    if squares.len() <= 2 {
+        return true;
+    }
 
-    for cy in (center.y - radius)..(center.y + radius + 1) {
-        for cx in (center.x - radius)..(center.x + radius + 1) {
-            let sq = GridSquare { y: cy, x: cx };
-            if distance(sq, center) <= radius {
-                result.push(sq);
-            }
+    squares[1..squares.len() - 1]
+        .iter()
+        .all(|sq| grid.free_square(*sq))
+}
+
+// Map from the squares of a grid to values, stored in a flat array
+// indexed by y * width + x.
+pub struct SquareMap<T> {
+    height: i32,
+    width: i32,
+    values: Vec<Option<T>>,
+    // Indices that have a value, in the order they were inserted
+    occupied: Vec<usize>,
+}
+
+impl<T> SquareMap<T> {
+    pub fn new(height: i32, width: i32) -> Self {
+        Self {
+            height,
+            width,
+            values: (0..height * width).map(|_| None).collect(),
+            occupied: Vec::new(),
         }
     }
 
-    result.sort_by_key(|k| distance(*k, center));
+    // Only touches the squares that have a value
+    pub fn clear(&mut self) {
+        for i in self.occupied.drain(..) {
+            self.values[i] = None;
+        }
+    }
 
-    result
+    fn index_of(&self, square: &GridSquare) -> Option<usize> {
+        let inside = (0..self.height).contains(&square.y) && (0..self.width).contains(&square.x);
+        inside.then(|| (square.y * self.width + square.x) as usize)
+    }
+
+    pub fn get(&self, square: &GridSquare) -> Option<&T> {
+        self.index_of(square).and_then(|i| self.values[i].as_ref())
+    }
+
+    pub fn contains_key(&self, square: &GridSquare) -> bool {
+        self.get(square).is_some()
+    }
+
+    pub fn insert(&mut self, square: GridSquare, value: T) {
+        let i = self
+            .index_of(&square)
+            .expect("the square should be inside the grid");
+        if self.values[i].replace(value).is_none() {
+            self.occupied.push(i);
+        }
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = GridSquare> + '_ {
+        let width = self.width;
+        self.occupied.iter().map(move |i| GridSquare {
+            y: *i as i32 / width,
+            x: *i as i32 % width,
+        })
+    }
+}
+
+impl<T> Index<&GridSquare> for SquareMap<T> {
+    type Output = T;
+
+    fn index(&self, square: &GridSquare) -> &T {
+        self.get(square).expect("the square should have a value")
+    }
 }
 
 pub struct ShortestPaths {
-    pub source: HashSet<GridSquare>,
-    pub distance: HashMap<GridSquare, i32>,
-    pub predecessor: HashMap<GridSquare, Option<GridSquare>>,
+    pub source: Vec<GridSquare>,
+    pub distance: SquareMap<i32>,
+    // Sources have no predecessor
+    pub predecessor: SquareMap<GridSquare>,
 }
 
-pub fn find_all_shortest_paths(
-    source: &Vec<GridSquare>,
-    max_distance: i32,
-    layer: &Layer,
-) -> ShortestPaths {
-    // [0]: even number of diagonals taken so far, next dist 1
-    // [1]: odd number of diagonals taken so far, next dist 2
-    let mut distance: Vec<HashMap<GridSquare, i32>> = vec![HashMap::new(), HashMap::new()];
-    let mut predecessor: Vec<HashMap<GridSquare, Option<GridSquare>>> =
-        vec![HashMap::new(), HashMap::new()];
-    let mut visited: Vec<HashSet<GridSquare>> = vec![HashSet::new(), HashSet::new()];
+// The maps go back to the scratch buffers for the next search
+impl Drop for ShortestPaths {
+    fn drop(&mut self) {
+        let distance = std::mem::replace(&mut self.distance, SquareMap::new(0, 0));
+        let predecessor = std::mem::replace(&mut self.predecessor, SquareMap::new(0, 0));
 
-    // (distance, (square, reached with even:0 / odd:1 number of diagonals))
-    let mut pq: BinaryHeap<(i32, (GridSquare, usize))> = BinaryHeap::new();
-    let inf: i32 = 1000000005;
+        let _ = SCRATCH.try_with(|scratch| {
+            if let Ok(mut scratch) = scratch.try_borrow_mut() {
+                scratch.spare_maps = Some((distance, predecessor));
+            }
+        });
+    }
+}
 
-    assert!(!source.is_empty());
+const NO_NODE: usize = usize::MAX;
+
+// Buffers for the path searches, kept between searches so that they
+// don't have to be allocated again. A node is a square together with the
+// parity of the diagonals taken to reach it:
+// node = 2 * (y * width + x) + parity
+// Parity 0 means the next diagonal costs 1, and parity 1 that it costs 2.
+struct Scratch {
+    height: i32,
+    width: i32,
+    // Incremented on every search. Entries stamped with an older
+    // generation are stale, so the buffers never need to be cleared.
+    generation: u32,
+    reached: Vec<u32>,
+    visited: Vec<u32>,
+    distance: Vec<i32>,
+    predecessor: Vec<usize>,
+    // Nodes in the order they were visited
+    visited_nodes: Vec<usize>,
+    // (-priority, node)
+    queue: BinaryHeap<(i32, usize)>,
+    // Maps of the last ShortestPaths that was dropped
+    spare_maps: Option<(SquareMap<i32>, SquareMap<GridSquare>)>,
+}
 
-    let mut creature_positions: HashSet<GridSquare> = HashSet::new();
+thread_local! {
+    static SCRATCH: RefCell<Scratch> = RefCell::new(Scratch::new());
+}
 
-    for (_, creature) in &layer.creatures {
-        creature_positions.insert(creature.get_position());
+impl Scratch {
+    fn new() -> Self {
+        Self {
+            height: 0,
+            width: 0,
+            generation: 0,
+            reached: Vec::new(),
+            visited: Vec::new(),
+            distance: Vec::new(),
+            predecessor: Vec::new(),
+            visited_nodes: Vec::new(),
+            queue: BinaryHeap::new(),
+            spare_maps: None,
+        }
     }
 
-    for sq in source.clone() {
-        distance[0].insert(sq, 0);
-        predecessor[0].insert(sq, None);
-        pq.push((0, (sq, 0)));
+    fn prepare(&mut self, layer: &Layer) {
+        let height = layer.grid.height();
+        let width = layer.grid.width();
+
+        if height != self.height || width != self.width || self.generation == u32::MAX {
+            let n_squares = (height * width) as usize;
+            self.height = height;
+            self.width = width;
+            self.generation = 0;
+            self.reached = vec![0; 2 * n_squares];
+            self.visited = vec![0; 2 * n_squares];
+            self.distance = vec![0; 2 * n_squares];
+            self.predecessor = vec![NO_NODE; 2 * n_squares];
+        }
+
+        self.generation += 1;
+        self.visited_nodes.clear();
+        self.queue.clear();
     }
 
-    while !pq.is_empty() {
-        let (_, (cur_square, diagonals)) = pq.pop().unwrap();
-        assert!(diagonals == 0 || diagonals == 1);
+    // Empty maps the size of the grid, reusing the spare ones if they fit
+    fn take_maps(&mut self) -> (SquareMap<i32>, SquareMap<GridSquare>) {
+        match self.spare_maps.take() {
+            Some((mut distance, mut predecessor))
+                if distance.height == self.height && distance.width == self.width =>
+            {
+                distance.clear();
+                predecessor.clear();
+                (distance, predecessor)
+            }
+            _ => (
+                SquareMap::new(self.height, self.width),
+                SquareMap::new(self.height, self.width),
+            ),
+        }
+    }
 
-        if visited[diagonals].contains(&cur_square) {
-            continue;
+    fn cell_of(&self, square: GridSquare) -> Option<usize> {
+        let inside = (0..self.height).contains(&square.y) && (0..self.width).contains(&square.x);
+        inside.then(|| (square.y * self.width + square.x) as usize)
+    }
+
+    fn square_of(&self, cell: usize) -> GridSquare {
+        GridSquare {
+            y: cell as i32 / self.width,
+            x: cell as i32 % self.width,
         }
-        visited[diagonals].insert(cur_square);
+    }
 
-        let neighbors = get_neighbors(cur_square);
-        for nb in neighbors {
-            // Blocked by a wall
-            if !layer.grid.free_square(nb) {
-                continue;
+    fn is_reached(&self, node: usize) -> bool {
+        self.reached[node] == self.generation
+    }
+
+    // Dijkstra's algorithm, or A* if there is a destination. The search
+    // stops when the destination is visited, and returns its node.
+    fn search(
+        &mut self,
+        source: &[GridSquare],
+        destination: Option<GridSquare>,
+        max_distance: i32,
+        layer: &Layer,
+    ) -> Option<usize> {
+        // Lower bound for the rest of the path, as if the way were clear
+        let estimate = |square: GridSquare, parity: usize| match destination {
+            Some(d) => {
+                let dy = (d.y - square.y).abs();
+                let dx = (d.x - square.x).abs();
+                let diagonals = cmp::min(dy, dx);
+                cmp::max(dy, dx) + (diagonals + parity as i32) / 2
             }
+            None => 0,
+        };
 
-            // Blocked by a creature
-            if creature_positions.contains(&nb) {
+        for sq in source {
+            if let Some(cell) = self.cell_of(*sq) {
+                let node = 2 * cell;
+                self.reached[node] = self.generation;
+                self.distance[node] = 0;
+                self.predecessor[node] = NO_NODE;
+                self.queue.push((-estimate(*sq, 0), node));
+            }
+        }
+
+        while let Some((_, node)) = self.queue.pop() {
+            if self.visited[node] == self.generation {
                 continue;
             }
+            self.visited[node] = self.generation;
+            self.visited_nodes.push(node);
 
-            if is_diagonal_neighbor(nb, cur_square) {
-                // If we have taken an even number of diagonals, the next one costs 1,
-                // otherwise it costs 2.
-                let dist_delta = 1 + diagonals as i32;
-                let new_dist = distance[diagonals].get(&cur_square).unwrap() + dist_delta;
+            let (cell, parity) = (node / 2, node % 2);
+            let cur_square = self.square_of(cell);
+            if destination == Some(cur_square) {
+                return Some(node);
+            }
 
-                if new_dist > max_distance {
-                    continue;
-                }
+            for (dy, dx) in NEIGHBOR_OFFSETS {
+                let nb = GridSquare {
+                    y: cur_square.y + dy,
+                    x: cur_square.x + dx,
+                };
 
-                let diagonals_nb = (diagonals + 1) % 2;
+                // Blocked by a wall or other impassable terrain
+                let terrain_cost = match layer.grid.get_tile(nb).and_then(|t| t.movement_cost()) {
+                    Some(cost) => cost,
+                    None => continue,
+                };
 
-                if new_dist < *distance[diagonals_nb].get(&nb).unwrap_or(&inf) {
-                    distance[diagonals_nb].insert(nb, new_dist);
-                    predecessor[diagonals_nb].insert(nb, Some(cur_square));
-                    pq.push((-new_dist, (nb, diagonals_nb)));
+                // Blocked by a creature, living or not
+                if !layer.creature_positions.get_at(nb).is_empty() {
+                    continue;
                 }
-            } else {
-                let dist_delta = 1;
-                let new_dist = distance[diagonals].get(&cur_square).unwrap() + dist_delta;
 
-                if new_dist > max_distance {
+                // If we have taken an even number of diagonals, the next one costs 1,
+                // otherwise it costs 2. Rough terrain adds its extra cost on top.
+                let (dist_delta, nb_parity) = if dy != 0 && dx != 0 {
+                    (parity as i32 + terrain_cost, 1 - parity)
+                } else {
+                    (terrain_cost, parity)
+                };
+
+                let new_dist = self.distance[node] + dist_delta;
+                let priority = new_dist + estimate(nb, nb_parity);
+                if priority > max_distance {
                     continue;
                 }
 
-                if new_dist < *distance[diagonals].get(&nb).unwrap_or(&inf) {
-                    distance[diagonals].insert(nb, new_dist);
-                    predecessor[diagonals].insert(nb, Some(cur_square));
-                    pq.push((-new_dist, (nb, diagonals)));
+                let nb_cell = self.cell_of(nb).unwrap();
+                let nb_node = 2 * nb_cell + nb_parity;
+                if !self.is_reached(nb_node) || new_dist < self.distance[nb_node] {
+                    self.reached[nb_node] = self.generation;
+                    self.distance[nb_node] = new_dist;
+                    self.predecessor[nb_node] = node;
+                    self.queue.push((-priority, nb_node));
                 }
             }
         }
+
+        None
     }
 
-    let mut source_set: HashSet<GridSquare> = HashSet::new();
-    for sq in source {
-        source_set.insert(*sq);
+    // Node of the square with the shorter distance, preferring parity 0
+    fn best_node(&self, cell: usize) -> usize {
+        let (even, odd) = (2 * cell, 2 * cell + 1);
+
+        if !self.is_reached(odd) {
+            even
+        } else if !self.is_reached(even) {
+            odd
+        } else if self.distance[even] <= self.distance[odd] {
+            even
+        } else {
+            odd
+        }
     }
+}
+
+pub fn find_all_shortest_paths(
+    source: &[GridSquare],
+    max_distance: i32,
+    layer: &Layer,
+) -> ShortestPaths {
+    assert!(!source.is_empty());
 
-    let visited_all: HashSet<_> = visited[0].union(&visited[1]).collect();
-    let mut distance_best: HashMap<GridSquare, i32> = HashMap::new();
-    let mut predecessor_best: HashMap<GridSquare, Option<GridSquare>> = HashMap::new();
+    SCRATCH.with_borrow_mut(|scratch| {
+        scratch.prepare(layer);
+        scratch.search(source, None, max_distance, layer);
 
-    for sq in visited_all {
-        let dist_even = *distance[0].get(&sq).unwrap_or(&inf);
-        let dist_odd = *distance[1].get(&sq).unwrap_or(&inf);
+        let (mut distance, mut predecessor) = scratch.take_maps();
 
-        assert!(dist_even != inf || dist_odd != inf);
+        for node in &scratch.visited_nodes {
+            let cell = node / 2;
+            let square = scratch.square_of(cell);
+            if distance.contains_key(&square) {
+                continue;
+            }
 
-        if dist_even <= dist_odd {
-            distance_best.insert(*sq, dist_even);
-            predecessor_best.insert(*sq, *predecessor[0].get(&sq).unwrap());
-        } else {
-            distance_best.insert(*sq, dist_odd);
-            predecessor_best.insert(*sq, *predecessor[1].get(&sq).unwrap());
+            let best = scratch.best_node(cell);
+            distance.insert(square, scratch.distance[best]);
+
+            if scratch.predecessor[best] != NO_NODE {
+                predecessor.insert(square, scratch.square_of(scratch.predecessor[best] / 2));
+            }
         }
-    }
 
-    ShortestPaths {
-        source: source_set,
-        distance: distance_best,
-        predecessor: predecessor_best,
-    }
+        ShortestPaths {
+            source: source.to_vec(),
+            distance,
+            predecessor,
+        }
+    })
 }
 
 pub fn get_reachable_squares(paths: &ShortestPaths) -> Vec<GridSquare> {
-    let mut reachable: Vec<GridSquare> = Vec::new();
-
-    for (sq, _) in &paths.distance {
-        reachable.push(*sq);
-    }
-
-    reachable
+    paths.distance.keys().collect()
 }
 
 pub fn get_shortest_path(paths: &ShortestPaths, target: GridSquare) -> Option<Vec<GridSquare>> {
     let mut result: Vec<GridSquare> = Vec::new();
 
-    if !paths.predecessor.contains_key(&target) {
+    if !paths.distance.contains_key(&target) {
         return None;
     }
 
     let mut cur_square = target;
     while !paths.source.contains(&cur_square) {
         result.push(cur_square);
-        cur_square = paths.predecessor.get(&cur_square).unwrap().unwrap();
+        cur_square = paths.predecessor[&cur_square];
     }
 
     result.push(cur_square);
@@ -212,10 +448,38 @@ pub fn get_shortest_path(paths: &ShortestPaths, target: GridSquare) -> Option<Ve
     Some(result)
 }
 
+// Shortest path between two squares, including both ends, or None if the
+// destination can't be reached within the distance. Cheaper than finding
+// the paths to every square when only one destination matters.
+pub fn find_shortest_path(
+    source: GridSquare,
+    destination: GridSquare,
+    max_distance: i32,
+    layer: &Layer,
+) -> Option<Vec<GridSquare>> {
+    SCRATCH.with_borrow_mut(|scratch| {
+        scratch.prepare(layer);
+        let mut node = scratch.search(&[source], Some(destination), max_distance, layer)?;
+
+        let mut result: Vec<GridSquare> = Vec::new();
+        while node != NO_NODE {
+            result.push(scratch.square_of(node / 2));
+            node = scratch.predecessor[node];
+        }
+        result.reverse();
+
+        Some(result)
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::grid::Tile;
+    use crate::ai::{Behavior, AI};
+    use crate::creature::statistics::Statistics;
+    use crate::creature::Creature;
+    use crate::grid::{DoorState, Tile};
+    use crate::world::Layer;
 
     fn test_distance_2way(a: GridSquare, b: GridSquare, expected: i32) {
         assert_eq!(distance(a, b), expected);
@@ -257,13 +521,13 @@ mod tests {
         // ...
         // *..
         // ...
-        let mut grid = Grid::new(3, 3);
-        grid.set_tile(GridSquare { y: 1, x: 0 }, Tile::Wall);
+        let mut layer = Layer::new(3, 3);
+        layer.grid.set_tile(GridSquare { y: 1, x: 0 }, Tile::Wall);
 
         let source = vec![GridSquare { y: 0, x: 0 }];
 
-        let all_paths = find_all_shortest_paths(&source, 3, &grid);
-        let path = extract_shortest_path(&all_paths, GridSquare { y: 2, x: 0 });
+        let all_paths = find_all_shortest_paths(&source, 3, &layer);
+        let path = get_shortest_path(&all_paths, GridSquare { y: 2, x: 0 });
         assert!(path.is_some());
         assert_eq!(
             path.unwrap(),
@@ -274,22 +538,97 @@ mod tests {
             ]
         );
 
-        let all_paths = find_all_shortest_paths(&source, 2, &grid);
-        let path = extract_shortest_path(&all_paths, GridSquare { y: 2, x: 0 });
+        let all_paths = find_all_shortest_paths(&source, 2, &layer);
+        let path = get_shortest_path(&all_paths, GridSquare { y: 2, x: 0 });
         assert!(path.is_none());
     }
 
+    #[test]
+    fn test_line_of_sight() {
+        // ...
+        // .+.
+        // ...
+        let mut layer = Layer::new(3, 3);
+        let door = GridSquare { y: 1, x: 1 };
+        layer.grid.set_tile(door, Tile::Door(DoorState::Closed));
+
+        let a = GridSquare { y: 1, x: 0 };
+        let b = GridSquare { y: 1, x: 2 };
+        assert!(!line_of_sight(&layer.grid, a, b));
+        assert!(line_of_sight(
+            &layer.grid,
+            GridSquare { y: 0, x: 0 },
+            GridSquare { y: 0, x: 2 }
+        ));
+        assert!(line_of_sight(&layer.grid, a, door));
+
+        layer.grid.set_tile(door, Tile::Door(DoorState::Open));
+        assert!(line_of_sight(&layer.grid, a, b));
+    }
+
+    #[test]
+    fn test_terrain_costs() {
+        // .~.
+        // .,.
+        // . .
+        let mut layer = Layer::new(3, 3);
+        layer.grid.set_tile(GridSquare { y: 0, x: 1 }, Tile::Water);
+        layer
+            .grid
+            .set_tile(GridSquare { y: 1, x: 1 }, Tile::DifficultTerrain);
+        layer.grid.set_tile(GridSquare { y: 2, x: 1 }, Tile::Chasm);
+
+        let source = vec![GridSquare { y: 0, x: 0 }];
+        let all_paths = find_all_shortest_paths(&source, 10, &layer);
+
+        assert_eq!(all_paths.distance[&GridSquare { y: 0, x: 1 }], 3);
+        assert_eq!(all_paths.distance[&GridSquare { y: 1, x: 1 }], 2);
+        assert_eq!(all_paths.distance[&GridSquare { y: 0, x: 2 }], 4);
+        assert!(!all_paths.distance.contains_key(&GridSquare { y: 2, x: 1 }));
+    }
+
+    #[test]
+    fn test_breaking_walls() {
+        // ..#..
+        // ..#..
+        // ..#..
+        let mut layer = Layer::new(3, 5);
+        for y in 0..3 {
+            layer.grid.set_tile(GridSquare { y, x: 2 }, Tile::Wall);
+        }
+
+        let wall = GridSquare { y: 1, x: 2 };
+        let a = GridSquare { y: 1, x: 1 };
+        let b = GridSquare { y: 1, x: 3 };
+
+        // The edges of the grid can't be broken
+        assert!(!layer.grid.destructible_square(GridSquare { y: 0, x: 2 }));
+        assert!(layer.grid.destructible_square(wall));
+
+        let max_hp = Tile::Wall.hit_points().unwrap();
+        assert!(!layer.grid.damage_tile(wall, max_hp - 1));
+        assert_eq!(layer.grid.get_tile_hp(wall), Some(1));
+        assert!(!line_of_sight(&layer.grid, a, b));
+
+        assert!(layer.grid.damage_tile(wall, 1));
+        assert!(layer.grid.get_tile(wall) == Some(Tile::Rubble));
+        assert!(line_of_sight(&layer.grid, a, b));
+
+        let all_paths = find_all_shortest_paths(&[a], 10, &layer);
+        assert!(get_shortest_path(&all_paths, b).is_some());
+    }
+
     #[test]
     fn test_unobstructed_shortest_distances() {
         let ny = 11;
         let nx = 11;
-        let grid = Grid::new(ny, nx);
+        let layer = Layer::new(ny, nx);
         let source = GridSquare {
             y: ny / 2,
             x: nx / 2,
         };
 
-        let all_paths = find_all_shortest_paths(&vec![source], ny, &grid);
+        let all_paths = find_all_shortest_paths(&[source], ny, &layer);
 
         for y in 0..11 {
             for x in 0..11 {
@@ -299,4 +638,108 @@ mod tests {
             }
         }
     }
+
+    // Cost of walking the path, which must go through neighboring squares
+    fn path_cost(path: &[GridSquare], layer: &Layer) -> i32 {
+        let mut cost = 0;
+        let mut diagonals = 0;
+
+        for step in path.windows(2) {
+            assert_eq!(distance(step[0], step[1]), 1);
+            cost += layer
+                .grid
+                .get_tile(step[1])
+                .unwrap()
+                .movement_cost()
+                .unwrap();
+
+            if step[0].y != step[1].y && step[0].x != step[1].x {
+                cost += diagonals % 2;
+                diagonals += 1;
+            }
+        }
+
+        cost
+    }
+
+    #[test]
+    fn test_find_shortest_path() {
+        // ....#....
+        // ....#....
+        // ....#.,,.
+        // ....#.,,.
+        // ....#....
+        // ..~......
+        // .....:...
+        let mut layer = Layer::new(7, 9);
+        for y in 0..5 {
+            layer.grid.set_tile(GridSquare { y, x: 4 }, Tile::Wall);
+        }
+        for (y, x) in [(2, 6), (2, 7), (3, 6), (3, 7)] {
+            layer
+                .grid
+                .set_tile(GridSquare { y, x }, Tile::DifficultTerrain);
+        }
+        layer.grid.set_tile(GridSquare { y: 5, x: 2 }, Tile::Water);
+        layer.grid.set_tile(GridSquare { y: 6, x: 5 }, Tile::Rubble);
+
+        let source = GridSquare { y: 0, x: 0 };
+
+        // The targeted search agrees with the search for all paths
+        for max_distance in [3, 8, 12, 30] {
+            let all_paths = find_all_shortest_paths(&[source], max_distance, &layer);
+
+            for y in 0..7 {
+                for x in 0..9 {
+                    let destination = GridSquare { y, x };
+                    let path = find_shortest_path(source, destination, max_distance, &layer);
+                    assert_eq!(
+                        path.is_some(),
+                        all_paths.distance.contains_key(&destination)
+                    );
+
+                    if let Some(path) = path {
+                        assert_eq!(path[0], source);
+                        assert_eq!(*path.last().unwrap(), destination);
+                        assert_eq!(path_cost(&path, &layer), all_paths.distance[&destination]);
+                    }
+                }
+            }
+        }
+
+        assert!(find_shortest_path(source, GridSquare { y: 0, x: 4 }, 30, &layer).is_none());
+    }
+
+    #[test]
+    fn test_creatures_block_paths() {
+        // .#.
+        // .c.
+        // .#.
+        let mut layer = Layer::new(3, 3);
+        layer.grid.set_tile(GridSquare { y: 0, x: 1 }, Tile::Wall);
+        layer.grid.set_tile(GridSquare { y: 2, x: 1 }, Tile::Wall);
+
+        let source = GridSquare { y: 1, x: 0 };
+        let all_paths = find_all_shortest_paths(&[source], 10, &layer);
+        assert_eq!(get_reachable_squares(&all_paths).len(), 7);
+        drop(all_paths);
+
+        // Dead creatures are in the way too
+        let position = GridSquare { y: 1, x: 1 };
+        let mut creature =
+            Creature::new(String::from("corpse"), position, Statistics::get_default());
+        let hp = creature.stats.current_hp;
+        creature.change_hp(-hp);
+        let c_id = creature.get_id();
+        layer.add_creature(
+            creature,
+            AI::new(c_id, Behavior::Inactive, Behavior::Inactive),
+        );
+
+        // The maps of the first search are reused without its results
+        let all_paths = find_all_shortest_paths(&[source], 10, &layer);
+        assert_eq!(get_reachable_squares(&all_paths).len(), 3);
+        assert!(!all_paths.distance.contains_key(&GridSquare { y: 1, x: 2 }));
+        assert!(find_shortest_path(source, GridSquare { y: 1, x: 2 }, 10, &layer).is_none());
+    }
 }
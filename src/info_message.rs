@@ -1,4 +1,6 @@
 use crate::check::Check;
+use crate::damage::Damage;
+use crate::dice::DiceRoll;
 use serde::{Deserialize, Serialize};
 
 #[derive(Deserialize, Serialize)]
@@ -6,13 +8,17 @@ pub enum MessageType {
     Info(String),
     Error(String),
     Attack(AttackMessage),
+    Experience(ExperienceMessage),
 }
 
 #[derive(Deserialize, Serialize)]
 pub struct AttackResult {
     pub target: u128,
     pub check: Check,
-    pub damage: i32,
+    // None if the attack missed and no damage was rolled. Critical hits
+    // double the rolled total.
+    pub roll: Option<DiceRoll>,
+    pub damage: Damage,
 }
 
 #[derive(Deserialize, Serialize)]
@@ -20,4 +26,15 @@ pub struct AttackMessage {
     pub attacker: u128,
     pub item: u128,
     pub results: Vec<AttackResult>,
+    // Walls and doors that the attack broke
+    pub broken_walls: i32,
+    pub broken_doors: i32,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct ExperienceMessage {
+    pub creature: u128,
+    pub defeated: u128,
+    pub experience: i32,
+    pub new_level: Option<i32>,
 }
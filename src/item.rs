@@ -1,17 +1,20 @@
+use crate::creature::equipment::EquipmentParams;
+use crate::item::generator::Affix;
 use crate::item::targeting::TargetKind;
 use rand::prelude::*;
 use serde::{Deserialize, Serialize};
 
+pub mod catalog;
 pub mod effect;
+pub mod generator;
 pub mod item_effects;
-pub mod item_types;
 pub mod statistics;
 pub mod targeting;
 
 #[derive(Clone, Deserialize, Serialize)]
 pub enum ItemKind {
     Active(TargetKind),
-    Passive,
+    Passive(EquipmentParams),
 }
 
 #[derive(Clone, Deserialize, Serialize)]
@@ -19,12 +22,24 @@ pub struct Item {
     pub name: String,
     pub description: String,
     pub kind: ItemKind,
+    // Remaining uses of a consumable item, None if it can be used forever
+    pub charges: Option<i32>,
+    // Actions it takes to use the item
+    pub action_cost: i32,
+    // Set by the item generator, already included in the item's stats
+    pub affixes: Vec<Affix>,
 
     id: u128,
 }
 
 impl Item {
-    pub fn new(name: String, description: String, kind: ItemKind) -> Self {
+    pub fn new(
+        name: String,
+        description: String,
+        kind: ItemKind,
+        charges: Option<i32>,
+        action_cost: i32,
+    ) -> Self {
         let mut rng = rand::thread_rng();
         let id = rng.gen();
 
@@ -33,9 +48,16 @@ impl Item {
             name,
             description,
             kind,
+            charges,
+            action_cost,
+            affixes: Vec::new(),
         }
     }
     pub fn get_id(&self) -> u128 {
         self.id
     }
+
+    pub fn is_used_up(&self) -> bool {
+        self.charges.is_some_and(|x| x <= 0)
+    }
 }
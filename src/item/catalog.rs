@@ -0,0 +1,533 @@
+use crate::constants;
+use crate::creature::equipment::{EquipmentParams, StatModifier};
+use crate::damage::DamageType;
+use crate::data::{self, DataError};
+use crate::dice::Dice;
+use crate::item::effect::{Duration, Effect};
+use crate::item::item_effects::{self, StatKind};
+use crate::item::statistics::{self, Rarity, Statistics};
+use crate::item::targeting::TargetKind;
+use crate::item::{Item, ItemKind};
+use crate::stat::Proficiency;
+use rand::distributions::WeightedIndex;
+use rand::prelude::*;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::Path;
+
+pub const CATALOG_FILE: &str = "items.ron";
+
+#[derive(Deserialize)]
+pub enum StatDefinition {
+    SingleTargetDamage(Proficiency),
+    AreaDamage(Proficiency),
+    // Damage dice given directly, such as "1d12+2". They grow with level
+    // like other damage.
+    Damage(Dice),
+    Healing(Proficiency),
+}
+
+impl StatDefinition {
+    pub fn add_to(&self, name: &str, stats: &mut Statistics) {
+        let name = String::from(name);
+
+        match self {
+            StatDefinition::SingleTargetDamage(prof) => {
+                let dice = statistics::new_single_target_damage(*prof);
+                stats.dice.insert(name, dice);
+            }
+            StatDefinition::AreaDamage(prof) => {
+                let dice = statistics::new_area_damage(*prof);
+                stats.dice.insert(name, dice);
+            }
+            StatDefinition::Damage(dice) => {
+                let dice = statistics::new_damage_dice(*dice);
+                stats.dice.insert(name, dice);
+            }
+            StatDefinition::Healing(prof) => {
+                stats.values.insert(name, statistics::new_healing(*prof));
+            }
+        }
+    }
+
+    pub fn get_kind(&self) -> StatKind {
+        match self {
+            StatDefinition::Healing(_) => StatKind::Value,
+            _ => StatKind::Dice,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct ItemDefinition {
+    pub name: String,
+    pub description: String,
+    pub kind: ItemKind,
+
+    // Identifier of the effect in item_effects::get_effect_definition.
+    // Required for active items, passive items have no effect.
+    #[serde(default = "no_effect")]
+    pub effect: String,
+    #[serde(default)]
+    pub stats: HashMap<String, StatDefinition>,
+    // Required for effects that deal damage
+    #[serde(default)]
+    pub damage_type: Option<DamageType>,
+
+    // Number of uses for consumable items
+    #[serde(default)]
+    pub charges: Option<i32>,
+    // Actions it takes to use the item
+    #[serde(default = "default_action_cost")]
+    pub action_cost: i32,
+
+    // Rarities the item can be created with
+    pub rarities: Vec<Rarity>,
+}
+
+fn default_action_cost() -> i32 {
+    1
+}
+
+fn no_effect() -> String {
+    String::from("none")
+}
+
+impl ItemDefinition {
+    pub fn allows_rarity(&self, rarity: Rarity) -> bool {
+        self.rarities.contains(&rarity)
+    }
+
+    // Picks one of the allowed rarities, weighted so that rarer ones come
+    // up less often
+    pub fn choose_rarity<R: Rng>(&self, rng: &mut R) -> Rarity {
+        let weights: Vec<u32> = self.rarities.iter().map(|r| r.get_weight()).collect();
+        let distribution = WeightedIndex::new(&weights)
+            .expect("items should have been validated to have rarities");
+
+        self.rarities[distribution.sample(rng)]
+    }
+
+    pub fn create(&self, level: i32, rarity: Rarity) -> (Item, Effect) {
+        let effect_definition = item_effects::get_effect_definition(&self.effect)
+            .expect("item effects should have been validated");
+
+        let mut stats = Statistics::new(level, rarity);
+        for (name, stat) in &self.stats {
+            stat.add_to(name, &mut stats);
+        }
+        stats.damage_type = self.damage_type;
+
+        let effect = Effect::new(
+            Duration::Instantaneous,
+            stats,
+            effect_definition.apply,
+            None,
+            None,
+        );
+
+        let item = Item::new(
+            self.name.clone(),
+            self.description.clone(),
+            self.kind.clone(),
+            self.charges,
+            self.action_cost,
+        );
+
+        (item, effect)
+    }
+
+    fn validate(&self) -> Result<(), DataError> {
+        let invalid = |msg: String| Err(DataError::Invalid(format!("{}: {}", self.name, msg)));
+
+        if self.name.is_empty() {
+            return Err(DataError::Invalid(String::from(
+                "item definition has no name",
+            )));
+        }
+
+        if self.rarities.is_empty() {
+            return invalid(String::from("no allowed rarities"));
+        }
+
+        if let Some(charges) = self.charges {
+            if charges <= 0 {
+                return invalid(format!("charges must be positive, got {}", charges));
+            }
+        }
+
+        if self.action_cost < 0 {
+            return invalid(format!(
+                "action cost must not be negative, got {}",
+                self.action_cost
+            ));
+        }
+
+        let effect_definition = match item_effects::get_effect_definition(&self.effect) {
+            Some(e) => e,
+            None => return invalid(format!("unknown effect \"{}\"", self.effect)),
+        };
+
+        for (stat, kind) in effect_definition.required_stats {
+            let expected = match kind {
+                StatKind::Dice => "dice",
+                StatKind::Value => "a value",
+            };
+
+            match self.stats.get(*stat) {
+                None => {
+                    return invalid(format!(
+                        "effect \"{}\" requires the stat \"{}\"",
+                        self.effect, stat
+                    ));
+                }
+                Some(definition) if definition.get_kind() != *kind => {
+                    return invalid(format!(
+                        "effect \"{}\" requires the stat \"{}\" to be {}",
+                        self.effect, stat, expected
+                    ));
+                }
+                _ => (),
+            }
+        }
+
+        let deals_damage = effect_definition
+            .required_stats
+            .iter()
+            .any(|(stat, _)| *stat == "damage");
+        if deals_damage && self.damage_type.is_none() {
+            return invalid(format!("effect \"{}\" requires a damage type", self.effect));
+        }
+        if !deals_damage && self.damage_type.is_some() {
+            return invalid(format!(
+                "effect \"{}\" does not deal damage, but a damage type is given",
+                self.effect
+            ));
+        }
+
+        match &self.kind {
+            ItemKind::Active(target_kind) => {
+                if self.effect == no_effect() {
+                    return invalid(String::from("active items need an effect"));
+                }
+
+                if let Err(msg) = validate_target_kind(*target_kind) {
+                    return invalid(msg);
+                }
+
+                let on_tiles = matches!(target_kind, TargetKind::Square(_) | TargetKind::Burst(_));
+                if effect_definition.breaks_tiles && !on_tiles {
+                    return invalid(format!(
+                        "effect \"{}\" only works on squares and bursts",
+                        self.effect
+                    ));
+                }
+            }
+            ItemKind::Passive(params) => {
+                if self.effect != no_effect() {
+                    return invalid(String::from("passive items can't have an effect"));
+                }
+
+                if self.charges.is_some() {
+                    return invalid(String::from("passive items can't have charges"));
+                }
+
+                if let Err(msg) = validate_equipment(params) {
+                    return invalid(msg);
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn validate_target_kind(target_kind: TargetKind) -> Result<(), String> {
+    let non_negative = |name: &str, value: i32| {
+        if value < 0 {
+            return Err(format!("{} must not be negative, got {}", name, value));
+        }
+        Ok(())
+    };
+    let positive = |name: &str, value: i32| {
+        if value <= 0 {
+            return Err(format!("{} must be positive, got {}", name, value));
+        }
+        Ok(())
+    };
+    // todo: remove once targeting::get_targeted_squares handles every kind
+    let unsupported = || Err(String::from("target kind is not supported yet"));
+
+    match target_kind {
+        TargetKind::Square(p) => non_negative("range", p.range),
+        TargetKind::Burst(p) => {
+            non_negative("range", p.range)?;
+            non_negative("radius", p.radius)
+        }
+        TargetKind::BurstVolumetric(p) => {
+            non_negative("range", p.range)?;
+            positive("volume", p.volume)?;
+            unsupported()
+        }
+        TargetKind::Cone(p) => {
+            positive("length", p.length)?;
+            unsupported()
+        }
+        TargetKind::Emanation(p) => {
+            positive("radius", p.radius)?;
+            unsupported()
+        }
+        TargetKind::Line(p) => {
+            positive("length", p.length)?;
+            positive("width", p.width)?;
+            unsupported()
+        }
+        TargetKind::LineReflecting(p) => {
+            positive("length", p.length)?;
+            positive("width", p.width)?;
+            unsupported()
+        }
+    }
+}
+
+fn validate_equipment(params: &EquipmentParams) -> Result<(), String> {
+    if params.modifiers.is_empty() {
+        return Err(String::from("equipment has no stat modifiers"));
+    }
+
+    // Modifiers are keyed by the item when equipped, so a second one of
+    // the same kind for the same stat would replace the first
+    for (i, modifier) in params.modifiers.iter().enumerate() {
+        let duplicate = params.modifiers[..i]
+            .iter()
+            .any(|other| match (other, modifier) {
+                (StatModifier::Additive(a, _), StatModifier::Additive(b, _)) => a == b,
+                (StatModifier::Multiplicative(a, _), StatModifier::Multiplicative(b, _)) => a == b,
+                _ => false,
+            });
+        if duplicate {
+            return Err(String::from(
+                "equipment has two modifiers of the same kind for one stat",
+            ));
+        }
+
+        if let StatModifier::Multiplicative(_, value) = modifier {
+            if *value <= 0.0 {
+                return Err(format!(
+                    "multiplicative modifiers must be positive, got {}",
+                    value
+                ));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+pub struct ItemCatalog {
+    items: Vec<ItemDefinition>,
+}
+
+impl ItemCatalog {
+    pub fn load(path: &Path) -> Result<Self, DataError> {
+        let items: Vec<ItemDefinition> = data::load(path)?;
+        Self::build(items)
+    }
+
+    pub fn load_default() -> Result<Self, DataError> {
+        let path = Path::new(constants::DATA_DIRECTORY).join(CATALOG_FILE);
+        Self::load(&path)
+    }
+
+    pub fn parse(catalog: &str) -> Result<Self, DataError> {
+        let items: Vec<ItemDefinition> = data::parse(catalog)?;
+        Self::build(items)
+    }
+
+    fn build(items: Vec<ItemDefinition>) -> Result<Self, DataError> {
+        let catalog = Self { items };
+        catalog.validate()?;
+
+        Ok(catalog)
+    }
+
+    fn validate(&self) -> Result<(), DataError> {
+        for (i, item) in self.items.iter().enumerate() {
+            item.validate()?;
+
+            if self.items[..i].iter().any(|x| x.name == item.name) {
+                return Err(DataError::Invalid(format!(
+                    "item \"{}\" is defined more than once",
+                    item.name
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn get(&self, name: &str) -> Option<&ItemDefinition> {
+        self.items.iter().find(|x| x.name == name)
+    }
+
+    pub fn get_all(&self) -> &Vec<ItemDefinition> {
+        &self.items
+    }
+
+    // Returns None if the item does not exist or can't have the given rarity.
+    pub fn create_item(&self, name: &str, level: i32, rarity: Rarity) -> Option<(Item, Effect)> {
+        let definition = self.get(name)?;
+
+        if !definition.allows_rarity(rarity) {
+            return None;
+        }
+
+        Some(definition.create(level, rarity))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::creature::character::Class;
+
+    const CATALOG: &str = include_str!("../../data/items.ron");
+
+    #[test]
+    fn test_catalog_is_valid() {
+        let catalog = ItemCatalog::parse(CATALOG);
+        assert!(catalog.is_ok(), "{}", catalog.err().unwrap());
+    }
+
+    #[test]
+    fn test_starting_items_exist() {
+        let catalog = ItemCatalog::parse(CATALOG).unwrap();
+
+        for class in Class::all() {
+            for item in class.get_starting_items() {
+                assert!(catalog.create_item(item, 1, Rarity::Common).is_some());
+            }
+        }
+    }
+
+    #[test]
+    fn test_invalid_definitions() {
+        let unknown_effect = r#"[(
+            name: "Banana",
+            description: "",
+            kind: Active(Square((range: 1))),
+            effect: "peel",
+            stats: {"damage": SingleTargetDamage(Low)},
+            rarities: [Common],
+        )]"#;
+        assert!(matches!(
+            ItemCatalog::parse(unknown_effect),
+            Err(DataError::Invalid(_))
+        ));
+
+        let negative_range = r#"[(
+            name: "Banana",
+            description: "",
+            kind: Active(Square((range: -1))),
+            effect: "melee_attack",
+            stats: {"damage": SingleTargetDamage(Low)},
+            damage_type: Some(Bludgeoning),
+            rarities: [Common],
+        )]"#;
+        assert!(matches!(
+            ItemCatalog::parse(negative_range),
+            Err(DataError::Invalid(_))
+        ));
+
+        let passive_with_effect = r#"[(
+            name: "Banana",
+            description: "",
+            kind: Passive((slot: Neck, modifiers: [Additive(WillDc, 1)])),
+            effect: "melee_attack",
+            stats: {"damage": SingleTargetDamage(Low)},
+            rarities: [Common],
+        )]"#;
+        assert!(matches!(
+            ItemCatalog::parse(passive_with_effect),
+            Err(DataError::Invalid(_))
+        ));
+
+        let active_without_effect = r#"[(
+            name: "Banana",
+            description: "",
+            kind: Active(Square((range: 1))),
+            rarities: [Common],
+        )]"#;
+        assert!(matches!(
+            ItemCatalog::parse(active_without_effect),
+            Err(DataError::Invalid(_))
+        ));
+
+        let duplicate_modifier = r#"[(
+            name: "Banana",
+            description: "",
+            kind: Passive((
+                slot: Neck,
+                modifiers: [Additive(WillDc, 1), Additive(WillDc, 2)],
+            )),
+            rarities: [Common],
+        )]"#;
+        assert!(matches!(
+            ItemCatalog::parse(duplicate_modifier),
+            Err(DataError::Invalid(_))
+        ));
+
+        let missing_stat = r#"[(
+            name: "Banana",
+            description: "",
+            kind: Active(Square((range: 1))),
+            effect: "melee_attack",
+            rarities: [Common],
+        )]"#;
+        assert!(matches!(
+            ItemCatalog::parse(missing_stat),
+            Err(DataError::Invalid(_))
+        ));
+
+        let missing_damage_type = r#"[(
+            name: "Banana",
+            description: "",
+            kind: Active(Square((range: 1))),
+            effect: "melee_attack",
+            stats: {"damage": SingleTargetDamage(Low)},
+            rarities: [Common],
+        )]"#;
+        assert!(matches!(
+            ItemCatalog::parse(missing_damage_type),
+            Err(DataError::Invalid(_))
+        ));
+
+        // Damage is read as dice and healing as a value
+        let healing_as_damage = r#"[(
+            name: "Banana",
+            description: "",
+            kind: Active(Square((range: 1))),
+            effect: "melee_attack",
+            stats: {"damage": Healing(Low)},
+            damage_type: Some(Bludgeoning),
+            rarities: [Common],
+        )]"#;
+        assert!(matches!(
+            ItemCatalog::parse(healing_as_damage),
+            Err(DataError::Invalid(_))
+        ));
+
+        let damage_as_healing = r#"[(
+            name: "Banana",
+            description: "",
+            kind: Active(Square((range: 1))),
+            effect: "heal",
+            stats: {"healing": Damage("2d6")},
+            rarities: [Common],
+        )]"#;
+        assert!(matches!(
+            ItemCatalog::parse(damage_as_healing),
+            Err(DataError::Invalid(_))
+        ));
+    }
+}
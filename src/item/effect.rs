@@ -1,3 +1,4 @@
+use crate::dice::Dice;
 use crate::grid::GridSquare;
 use crate::info_message::MessageType;
 use crate::item::statistics::Statistics;
@@ -43,6 +44,9 @@ pub struct EffectResult {
     pub message: MessageType,
 }
 
+#[rustfmt::skip]
+pub type ApplyFn = fn(effect: u128, owner: u128, target: GridSquare, layer: &mut Layer) -> EffectResult;
+
 pub struct Effect {
     pub duration: Duration,
     pub stats: Statistics,
@@ -92,7 +96,61 @@ impl Effect {
         stat_value
     }
 
+    // Dice at the effect's level
+    pub fn get_dice(&self, value: &str) -> Dice {
+        let level = self.stats.get_level();
+
+        self.stats
+            .dice
+            .get(value)
+            .expect("effect functions should only access existing dice")
+            .get_dice(level)
+    }
+
     pub fn get_id(&self) -> u128 {
         self.id
     }
 }
+
+// Counts down the temporary ongoing effects at the end of a round and
+// ends the ones that have run out.
+pub fn tick_ongoing_effects(layer: &mut Layer) -> Vec<MessageType> {
+    let mut messages: Vec<MessageType> = Vec::new();
+    let ongoing_ids: Vec<u128> = layer.ongoing_effects.keys().copied().collect();
+
+    for ongoing_id in ongoing_ids {
+        let ongoing = layer.ongoing_effects.get_mut(&ongoing_id).unwrap();
+        let (effect_id, owner, target) = (ongoing.effect, ongoing.owner, ongoing.target);
+
+        let expired = match &mut ongoing.remaining_duration {
+            Duration::Temporary(rounds) => {
+                *rounds -= 1;
+                *rounds <= 0
+            }
+            _ => false,
+        };
+
+        let effect = layer
+            .effects
+            .get(&effect_id)
+            .expect("an ongoing effect should have an effect");
+        let (tick, remove) = (effect.tick, effect.remove);
+
+        if let Some(tick) = tick {
+            messages.push(tick(effect_id, owner, target, layer));
+        }
+
+        if expired {
+            if let Some(remove) = remove {
+                messages.push(remove(effect_id, owner, target, layer));
+            }
+
+            layer.ongoing_effects.remove(&ongoing_id);
+        }
+    }
+
+    // Effects that had nothing left to act on have nothing to report
+    messages.retain(|m| !matches!(m, MessageType::Info(text) if text.is_empty()));
+
+    messages
+}
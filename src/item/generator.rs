@@ -0,0 +1,293 @@
+use crate::creature::equipment::StatModifier;
+use crate::creature::statistics::CreatureStat;
+use crate::damage::DamageType;
+use crate::item::catalog::{ItemCatalog, ItemDefinition};
+use crate::item::effect::Effect;
+use crate::item::statistics::Rarity;
+use crate::item::{Item, ItemKind};
+use rand::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+// Stats that equipment affixes can raise, with the name suffix they give
+const BONUS_STATS: [(CreatureStat, &str); 9] = [
+    (CreatureStat::MaxHp, "Vitality"),
+    (CreatureStat::ArmorClass, "Warding"),
+    (CreatureStat::FortitudeDc, "Fortitude"),
+    (CreatureStat::ReflexDc, "Evasion"),
+    (CreatureStat::WillDc, "Resolve"),
+    (CreatureStat::MeleeAttack, "Might"),
+    (CreatureStat::RangedAttack, "Aim"),
+    (CreatureStat::MagicAttack, "Sorcery"),
+    (CreatureStat::Initiative, "Alertness"),
+];
+
+const ELEMENTAL_TYPES: [DamageType; 4] = [
+    DamageType::Fire,
+    DamageType::Cold,
+    DamageType::Electricity,
+    DamageType::Poison,
+];
+
+// Max HP bonuses would be negligible at the same scale as the others
+const MAX_HP_BONUS_MULTIPLIER: i32 = 5;
+
+// Random property that sets a generated item apart from its base item
+#[derive(Clone, Copy, Deserialize, PartialEq, Serialize)]
+pub enum Affix {
+    // Bonus to attack rolls made with the item
+    AttackBonus(i32),
+    // Replaces the damage type of the item
+    Elemental(DamageType),
+    ExtraCharges(i32),
+    // Bonus to a stat of the creature that equips the item
+    StatBonus(CreatureStat, i32),
+}
+
+impl Affix {
+    fn get_prefix(&self) -> Option<String> {
+        match self {
+            Affix::AttackBonus(bonus) => Some(format!("{:+}", bonus)),
+            Affix::Elemental(damage_type) => {
+                let adjective = match damage_type {
+                    DamageType::Fire => String::from("Flaming"),
+                    DamageType::Cold => String::from("Frost"),
+                    DamageType::Electricity => String::from("Shocking"),
+                    DamageType::Poison => String::from("Venomous"),
+                    other => other.to_string(),
+                };
+                Some(adjective)
+            }
+            _ => None,
+        }
+    }
+
+    fn get_suffix(&self) -> Option<&'static str> {
+        match self {
+            Affix::ExtraCharges(_) => Some("Plenty"),
+            Affix::StatBonus(stat, _) => BONUS_STATS
+                .iter()
+                .find(|(s, _)| s == stat)
+                .map(|(_, suffix)| *suffix),
+            _ => None,
+        }
+    }
+}
+
+fn describe_stat(stat: CreatureStat) -> &'static str {
+    match stat {
+        CreatureStat::MaxHp => "max HP",
+        CreatureStat::ArmorClass => "armor class",
+        CreatureStat::FortitudeDc => "fortitude",
+        CreatureStat::ReflexDc => "reflex",
+        CreatureStat::WillDc => "will",
+        CreatureStat::MeleeAttack => "melee attack",
+        CreatureStat::RangedAttack => "ranged attack",
+        CreatureStat::MagicAttack => "magic attack",
+        CreatureStat::MovementSpeed => "speed",
+        CreatureStat::Initiative => "initiative",
+        CreatureStat::NActions => "actions",
+    }
+}
+
+impl fmt::Display for Affix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Affix::AttackBonus(bonus) => write!(f, "{:+} attack", bonus),
+            Affix::Elemental(damage_type) => write!(f, "{} damage", damage_type),
+            Affix::ExtraCharges(charges) => write!(f, "{:+} charges", charges),
+            Affix::StatBonus(stat, bonus) => write!(f, "{:+} {}", bonus, describe_stat(*stat)),
+        }
+    }
+}
+
+fn get_affix_count(rarity: Rarity) -> usize {
+    match rarity {
+        Rarity::Common => 0,
+        Rarity::Uncommon | Rarity::Rare => 1,
+        Rarity::VeryRare => 2,
+        Rarity::Legendary => 3,
+    }
+}
+
+// Every affix the item could get, with the values already rolled. Rarer
+// items roll bigger values.
+fn get_candidates<R: Rng>(item: &Item, effect: &Effect, rarity: Rarity, rng: &mut R) -> Vec<Affix> {
+    let max_value = rarity.get_tier().max(1);
+    let mut candidates: Vec<Affix> = Vec::new();
+
+    match &item.kind {
+        ItemKind::Active(_) => {
+            if let Some(damage_type) = effect.stats.damage_type {
+                candidates.push(Affix::AttackBonus(rng.gen_range(1..=max_value)));
+
+                let elements: Vec<DamageType> = ELEMENTAL_TYPES
+                    .into_iter()
+                    .filter(|t| *t != damage_type)
+                    .collect();
+                candidates.push(Affix::Elemental(*elements.choose(rng).unwrap()));
+            }
+
+            if item.charges.is_some() {
+                candidates.push(Affix::ExtraCharges(rng.gen_range(1..=max_value)));
+            }
+        }
+        ItemKind::Passive(_) => {
+            for (stat, _) in BONUS_STATS {
+                let mut bonus = rng.gen_range(1..=max_value);
+                if stat == CreatureStat::MaxHp {
+                    bonus *= MAX_HP_BONUS_MULTIPLIER;
+                }
+
+                candidates.push(Affix::StatBonus(stat, bonus));
+            }
+        }
+    }
+
+    candidates
+}
+
+fn apply_affix(affix: Affix, item: &mut Item, effect: &mut Effect) {
+    match affix {
+        Affix::AttackBonus(bonus) => effect.stats.attack_bonus += bonus,
+        Affix::Elemental(damage_type) => effect.stats.damage_type = Some(damage_type),
+        Affix::ExtraCharges(charges) => {
+            item.charges = item.charges.map(|c| c + charges);
+        }
+        // Equipping keys every modifier of the item by the item's id, so a
+        // stat can only have one additive modifier per item
+        Affix::StatBonus(stat, bonus) => {
+            if let ItemKind::Passive(params) = &mut item.kind {
+                let existing = params.modifiers.iter_mut().find_map(|m| match m {
+                    StatModifier::Additive(s, value) if *s == stat => Some(value),
+                    _ => None,
+                });
+
+                match existing {
+                    Some(value) => *value += bonus,
+                    None => params.modifiers.push(StatModifier::Additive(stat, bonus)),
+                }
+            }
+        }
+    }
+}
+
+// Names read as "+1 Flaming Longsword of Might and Warding"
+fn get_name(base_name: &str, affixes: &[Affix]) -> String {
+    let mut words: Vec<String> = affixes.iter().filter_map(|a| a.get_prefix()).collect();
+    words.push(String::from(base_name));
+
+    let suffixes: Vec<&str> = affixes.iter().filter_map(|a| a.get_suffix()).collect();
+    if !suffixes.is_empty() {
+        words.push(format!("of {}", suffixes.join(" and ")));
+    }
+
+    words.join(" ")
+}
+
+// Creates the base item with random affixes. The number of affixes
+// depends on the rarity, so common items are identical to the base item.
+pub fn generate<R: Rng>(
+    definition: &ItemDefinition,
+    level: i32,
+    rarity: Rarity,
+    rng: &mut R,
+) -> (Item, Effect) {
+    let (mut item, mut effect) = definition.create(level, rarity);
+
+    let candidates = get_candidates(&item, &effect, rarity, rng);
+    let mut affixes: Vec<Affix> = candidates
+        .choose_multiple(rng, get_affix_count(rarity))
+        .copied()
+        .collect();
+
+    // Prefixes come out in the same order regardless of the roll
+    affixes.sort_by_key(|a| !matches!(a, Affix::AttackBonus(_)));
+
+    for affix in &affixes {
+        apply_affix(*affix, &mut item, &mut effect);
+    }
+
+    item.name = get_name(&definition.name, &affixes);
+    item.affixes = affixes;
+
+    (item, effect)
+}
+
+// Random item of a random allowed rarity at the given level
+pub fn generate_random<R: Rng>(catalog: &ItemCatalog, level: i32, rng: &mut R) -> (Item, Effect) {
+    let definition = catalog
+        .get_all()
+        .choose(rng)
+        .expect("the catalog should not be empty");
+    let rarity = definition.choose_rarity(rng);
+
+    generate(definition, level, rarity, rng)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::creature::statistics::Statistics;
+    use crate::creature::Creature;
+    use crate::grid::GridSquare;
+    use rand_chacha::ChaCha20Rng;
+
+    const CATALOG: &str = include_str!("../../data/items.ron");
+
+    #[test]
+    fn test_generate() {
+        let catalog = ItemCatalog::parse(CATALOG).unwrap();
+        let mut rng = ChaCha20Rng::seed_from_u64(0);
+
+        let longsword = catalog.get("Longsword").unwrap();
+        let (item, _) = generate(longsword, 1, Rarity::Common, &mut rng);
+        assert_eq!(item.name, "Longsword");
+        assert!(item.affixes.is_empty());
+
+        let (item, effect) = generate(longsword, 1, Rarity::Legendary, &mut rng);
+        assert_eq!(item.affixes.len(), 2);
+        assert_ne!(item.name, "Longsword");
+        assert!(effect.stats.attack_bonus > 0);
+
+        let ring = catalog.get("Ring of protection").unwrap();
+        let (item, _) = generate(ring, 1, Rarity::Legendary, &mut rng);
+        assert_eq!(item.affixes.len(), 3);
+        match &item.kind {
+            ItemKind::Passive(params) => assert!(params.modifiers.len() >= 3),
+            _ => panic!("ring should be passive"),
+        }
+
+        // The same seed gives the same item
+        let mut a = ChaCha20Rng::seed_from_u64(1);
+        let mut b = ChaCha20Rng::seed_from_u64(1);
+        let potion = catalog.get("Healing potion").unwrap();
+        assert_eq!(
+            generate(potion, 1, Rarity::Rare, &mut a).0.name,
+            generate(potion, 1, Rarity::Rare, &mut b).0.name
+        );
+    }
+
+    #[test]
+    fn test_stat_bonus_adds_to_base_modifier() {
+        let catalog = ItemCatalog::parse(CATALOG).unwrap();
+        let chain_mail = catalog.get("Chain mail").unwrap();
+        let (mut item, mut effect) = chain_mail.create(1, Rarity::Rare);
+        let bonus = Affix::StatBonus(CreatureStat::ArmorClass, 1);
+        apply_affix(bonus, &mut item, &mut effect);
+
+        let position = GridSquare { y: 0, x: 0 };
+        let mut creature = Creature::new(
+            String::from("creature"),
+            position,
+            Statistics::get_default(),
+        );
+        let base_ac = creature.stats.armor_class.get_value(1);
+
+        match &item.kind {
+            ItemKind::Passive(params) => creature.equip_item(item.get_id(), params),
+            _ => panic!("chain mail should be passive"),
+        }
+        assert_eq!(creature.stats.armor_class.get_value(1), base_ac + 3);
+    }
+}
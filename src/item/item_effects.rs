@@ -1,30 +1,100 @@
-use crate::check::{self, Outcome};
-use crate::grid::GridSquare;
+use crate::check::{self, Check, Circumstance, Outcome};
+use crate::creature::positioning;
+use crate::creature::statistics::{CreatureStat, Statistics as CreatureStatistics};
+use crate::damage;
+use crate::grid::{GridSquare, Tile};
 use crate::info_message::{AttackMessage, AttackResult, MessageType};
-use crate::item::effect::{Duration, Effect, EffectResult};
-use crate::item::statistics::{self, Rarity, Statistics};
+use crate::item::effect::{ApplyFn, EffectResult};
 use crate::item::targeting::{self, TargetKind};
 use crate::item::ItemKind;
-use crate::stat::Proficiency;
 use crate::world::Layer;
 
+// Explosions are far better at tearing down walls than at hurting creatures
+const EXPLOSION_TILE_DAMAGE_MULTIPLIER: i32 = 5;
+
 fn get_target_kind(item_id: u128, layer: &Layer) -> TargetKind {
     let item = layer.items.get(&item_id).unwrap();
     match item.kind {
         ItemKind::Active(target_kind) => target_kind,
-        ItemKind::Passive => panic!("passive items have no targets"),
+        ItemKind::Passive(_) => panic!("passive items have no targets"),
     }
 }
 
-pub fn create_testeffect(level: i32, rarity: Rarity) -> Effect {
-    let duration = Duration::Instantaneous;
-    let mut stats = Statistics::new(level, rarity);
-    stats.values.insert(
-        "damage".to_string(),
-        statistics::new_single_target_damage(Proficiency::High),
-    );
+// How an effect reads a stat
+#[derive(Clone, Copy, PartialEq)]
+pub enum StatKind {
+    // Through Effect::get_dice
+    Dice,
+    // Through Effect::get_stat_value
+    Value,
+}
 
-    Effect::new(duration, stats, basic_melee_attack, None, None)
+pub struct EffectDefinition {
+    pub apply: ApplyFn,
+    // Stats that the effect reads, and how it reads them
+    pub required_stats: &'static [(&'static str, StatKind)],
+    // Effects that break walls and doors only work on squares and bursts
+    pub breaks_tiles: bool,
+}
+
+// Effects that data files can refer to by their identifier.
+pub fn get_effect_definition(identifier: &str) -> Option<EffectDefinition> {
+    match identifier {
+        "none" => Some(EffectDefinition {
+            apply: no_effect,
+            required_stats: &[],
+            breaks_tiles: false,
+        }),
+        "melee_attack" => Some(EffectDefinition {
+            apply: basic_melee_attack,
+            required_stats: &[("damage", StatKind::Dice)],
+            breaks_tiles: false,
+        }),
+        "ranged_attack" => Some(EffectDefinition {
+            apply: basic_ranged_attack,
+            required_stats: &[("damage", StatKind::Dice)],
+            breaks_tiles: false,
+        }),
+        "magic_attack" => Some(EffectDefinition {
+            apply: basic_magic_attack,
+            required_stats: &[("damage", StatKind::Dice)],
+            breaks_tiles: false,
+        }),
+        "ranged_area_attack" => Some(EffectDefinition {
+            apply: ranged_area_attack,
+            required_stats: &[("damage", StatKind::Dice)],
+            breaks_tiles: false,
+        }),
+        "magic_area_attack" => Some(EffectDefinition {
+            apply: magic_area_attack,
+            required_stats: &[("damage", StatKind::Dice)],
+            breaks_tiles: false,
+        }),
+        "explosion" => Some(EffectDefinition {
+            apply: explosion,
+            required_stats: &[("damage", StatKind::Dice)],
+            breaks_tiles: true,
+        }),
+        "heal" => Some(EffectDefinition {
+            apply: heal,
+            required_stats: &[("healing", StatKind::Value)],
+            breaks_tiles: false,
+        }),
+        _ => None,
+    }
+}
+
+// Effect of items that can't be activated, such as equipment
+pub fn no_effect(
+    _effect_id: u128,
+    _owner_id: u128,
+    _target: GridSquare,
+    _layer: &mut Layer,
+) -> EffectResult {
+    EffectResult {
+        ongoing_effect: None,
+        message: MessageType::Info(String::from("Nothing happens")),
+    }
 }
 
 pub fn basic_melee_attack(
@@ -33,8 +103,226 @@ pub fn basic_melee_attack(
     target: GridSquare,
     layer: &mut Layer,
 ) -> EffectResult {
+    basic_attack(
+        effect_id,
+        attacker_id,
+        target,
+        layer,
+        check::melee_attack_roll,
+    )
+}
+
+pub fn basic_ranged_attack(
+    effect_id: u128,
+    attacker_id: u128,
+    target: GridSquare,
+    layer: &mut Layer,
+) -> EffectResult {
+    basic_attack(
+        effect_id,
+        attacker_id,
+        target,
+        layer,
+        check::ranged_attack_roll,
+    )
+}
+
+pub fn basic_magic_attack(
+    effect_id: u128,
+    attacker_id: u128,
+    target: GridSquare,
+    layer: &mut Layer,
+) -> EffectResult {
+    basic_attack(
+        effect_id,
+        attacker_id,
+        target,
+        layer,
+        check::magic_attack_roll,
+    )
+}
+
+pub fn ranged_area_attack(
+    effect_id: u128,
+    attacker_id: u128,
+    target: GridSquare,
+    layer: &mut Layer,
+) -> EffectResult {
+    basic_attack(
+        effect_id,
+        attacker_id,
+        target,
+        layer,
+        check::ranged_reflex_roll,
+    )
+}
+
+pub fn magic_area_attack(
+    effect_id: u128,
+    attacker_id: u128,
+    target: GridSquare,
+    layer: &mut Layer,
+) -> EffectResult {
+    basic_attack(
+        effect_id,
+        attacker_id,
+        target,
+        layer,
+        check::magic_reflex_roll,
+    )
+}
+
+// Area attack that also damages the walls and doors around it
+pub fn explosion(
+    effect_id: u128,
+    attacker_id: u128,
+    target: GridSquare,
+    layer: &mut Layer,
+) -> EffectResult {
+    let mut result = basic_attack(
+        effect_id,
+        attacker_id,
+        target,
+        layer,
+        check::ranged_reflex_roll,
+    );
+
     let effect = layer.effects.get(&effect_id).unwrap();
-    let item_damage = effect.get_stat_value("damage");
+    let tile_damage = effect
+        .get_dice("damage")
+        .roll(&mut rand::thread_rng())
+        .total
+        * EXPLOSION_TILE_DAMAGE_MULTIPLIER;
+
+    let target_kind = get_target_kind(effect_id, layer);
+    let (mut broken_walls, mut broken_doors) = (0, 0);
+
+    for square in targeting::get_targeted_tiles(target, target_kind, layer) {
+        let tile = layer.grid.get_tile(square);
+
+        if layer.grid.damage_tile(square, tile_damage) {
+            match tile {
+                Some(Tile::Door(_)) => broken_doors += 1,
+                _ => broken_walls += 1,
+            }
+        }
+    }
+
+    if let MessageType::Attack(message) = &mut result.message {
+        message.broken_walls = broken_walls;
+        message.broken_doors = broken_doors;
+    }
+
+    result
+}
+
+pub fn heal(
+    effect_id: u128,
+    healer_id: u128,
+    target: GridSquare,
+    layer: &mut Layer,
+) -> EffectResult {
+    let effect = layer.effects.get(&effect_id).unwrap();
+    let healing = effect.get_stat_value("healing");
+
+    let target_kind = get_target_kind(effect_id, layer);
+    #[rustfmt::skip]
+    let target_squares = targeting::get_targeted_squares(target, target_kind, healer_id, layer).expect("target should be in range");
+
+    // Healing also revives downed creatures
+    let mut affected_creatures = Vec::new();
+    for square in target_squares {
+        affected_creatures.append(&mut layer.get_living_creatures_at(square));
+        affected_creatures.append(&mut layer.get_downed_creatures_at(square));
+    }
+
+    let mut healed: Vec<String> = Vec::new();
+
+    for c in affected_creatures {
+        let creature = layer.creatures.get_mut(&c).unwrap();
+
+        let was_downed = creature.is_downed();
+        let hp_before = creature.stats.current_hp;
+        creature.change_hp(healing);
+        let hp_gained = creature.stats.current_hp - hp_before;
+
+        if was_downed && creature.is_alive() {
+            healed.push(format!(
+                "{} regains {} HP and gets back up",
+                creature.name, hp_gained
+            ));
+        } else {
+            healed.push(format!("{} regains {} HP", creature.name, hp_gained));
+        }
+    }
+
+    let message = if healed.is_empty() {
+        String::from("Nobody is healed")
+    } else {
+        healed.join(", ")
+    };
+
+    EffectResult {
+        ongoing_effect: None,
+        message: MessageType::Info(message),
+    }
+}
+
+// Circumstances modify the defenses that attacks are rolled against for
+// the duration of a single attack roll
+fn set_circumstances(
+    defender_id: u128,
+    circumstances: &[Circumstance],
+    layer: &mut Layer,
+    active: bool,
+) {
+    let defender = layer.creatures.get_mut(&defender_id).unwrap();
+
+    for c in circumstances {
+        for stat in [CreatureStat::ArmorClass, CreatureStat::ReflexDc] {
+            let stat = defender.stats.get_stat_mut(stat);
+            if active {
+                stat.apply_additive_modifier(c.get_key(), c.get_dc_modifier());
+            } else {
+                stat.remove_modifier(c.get_key());
+            }
+        }
+    }
+}
+
+// Item attack bonuses apply to whichever attack stat the roll uses. The
+// item id doubles as the modifier key.
+fn set_attack_bonus(attacker_id: u128, item_id: u128, bonus: i32, layer: &mut Layer, active: bool) {
+    let attacker = layer.creatures.get_mut(&attacker_id).unwrap();
+
+    for stat in [
+        CreatureStat::MeleeAttack,
+        CreatureStat::RangedAttack,
+        CreatureStat::MagicAttack,
+    ] {
+        let stat = attacker.stats.get_stat_mut(stat);
+        if active {
+            stat.apply_additive_modifier(item_id, bonus);
+        } else {
+            stat.remove_modifier(item_id);
+        }
+    }
+}
+
+fn basic_attack(
+    effect_id: u128,
+    attacker_id: u128,
+    target: GridSquare,
+    layer: &mut Layer,
+    attack_roll: fn(&CreatureStatistics, &CreatureStatistics) -> Check,
+) -> EffectResult {
+    let effect = layer.effects.get(&effect_id).unwrap();
+    let item_damage = effect.get_dice("damage");
+    let attack_bonus = effect.stats.attack_bonus;
+    let damage_type = effect
+        .stats
+        .damage_type
+        .expect("effects that deal damage should have a damage type");
 
     let target_kind = get_target_kind(effect_id, layer);
     #[rustfmt::skip]
@@ -42,29 +330,53 @@ pub fn basic_melee_attack(
 
     let affected_creatures = targeting::get_affected_creatures(target_squares, layer);
 
+    // Area attacks spread out from where they land
+    let origin = match target_kind {
+        TargetKind::Burst(_) => target,
+        _ => layer.creatures.get(&attacker_id).unwrap().get_position(),
+    };
+
     let mut results: Vec<AttackResult> = Vec::new();
+    let mut rng = rand::thread_rng();
 
     for c in affected_creatures {
+        let circumstances = positioning::get_circumstances(origin, attacker_id, c, layer);
+        set_circumstances(c, &circumstances, layer, true);
+        set_attack_bonus(attacker_id, effect_id, attack_bonus, layer, true);
+
         // Need to get attacker here instead of only once outside the loop
         // to satisfy the borrow checker
         let attacker = layer.creatures.get(&attacker_id).unwrap();
         let defender = layer.creatures.get(&c).unwrap();
-        let check = check::melee_attack_roll(&attacker.stats, &defender.stats);
+        let mut check = attack_roll(&attacker.stats, &defender.stats);
+        check.circumstances = circumstances.clone();
+
+        set_circumstances(c, &circumstances, layer, false);
+        set_attack_bonus(attacker_id, effect_id, attack_bonus, layer, false);
+
         let damage_multiplier = match check.outcome {
             Outcome::CriticalSuccess => 2,
             Outcome::Success => 1,
             _ => 0,
         };
 
-        let attack_damage = item_damage * damage_multiplier;
+        // Damage is only rolled for hits
+        let roll = (damage_multiplier > 0).then(|| item_damage.roll(&mut rng));
+        let rolled_damage = roll.as_ref().map_or(0, |r| r.total);
 
         let defender = layer.creatures.get_mut(&c).unwrap();
-        defender.change_hp(-attack_damage);
+        let damage = damage::calculate(
+            rolled_damage * damage_multiplier,
+            damage_type,
+            &defender.stats,
+        );
+        defender.change_hp(-damage.total);
 
         let result = AttackResult {
             target: c,
             check,
-            damage: attack_damage,
+            roll,
+            damage,
         };
 
         results.push(result);
@@ -75,6 +387,8 @@ pub fn basic_melee_attack(
         attacker: attacker_id,
         item: effect_id,
         results,
+        broken_walls: 0,
+        broken_doors: 0,
     };
 
     EffectResult {
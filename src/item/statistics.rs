@@ -1,8 +1,10 @@
+use crate::damage::DamageType;
+use crate::dice::{Dice, ScaledDice};
 use crate::stat::{LevelScaling, Proficiency, Stat};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-#[derive(Copy, Clone, Deserialize, Serialize)]
+#[derive(Copy, Clone, Deserialize, PartialEq, Serialize)]
 pub enum Rarity {
     Common,
     Uncommon,
@@ -11,10 +13,41 @@ pub enum Rarity {
     Legendary,
 }
 
+impl Rarity {
+    // Common is tier 0 and each rarer step adds one
+    pub fn get_tier(&self) -> i32 {
+        match self {
+            Rarity::Common => 0,
+            Rarity::Uncommon => 1,
+            Rarity::Rare => 2,
+            Rarity::VeryRare => 3,
+            Rarity::Legendary => 4,
+        }
+    }
+
+    // Relative probability of rolling the rarity for random loot. Each
+    // step up is half as likely as the one before it.
+    pub fn get_weight(&self) -> u32 {
+        match self {
+            Rarity::Common => 16,
+            Rarity::Uncommon => 8,
+            Rarity::Rare => 4,
+            Rarity::VeryRare => 2,
+            Rarity::Legendary => 1,
+        }
+    }
+}
+
 #[derive(Clone, Deserialize, Serialize)]
 pub struct Statistics {
     pub rarity: Rarity,
     pub values: HashMap<String, Stat>,
+    // Stats that are rolled, such as damage
+    pub dice: HashMap<String, ScaledDice>,
+    // Type of the damage stat, for effects that deal damage
+    pub damage_type: Option<DamageType>,
+    // Added to the attack rolls of attack effects
+    pub attack_bonus: i32,
 
     level: i32,
 }
@@ -25,6 +58,9 @@ impl Statistics {
             level: level,
             rarity: rarity,
             values: HashMap::new(),
+            dice: HashMap::new(),
+            damage_type: None,
+            attack_bonus: 0,
         }
     }
 
@@ -33,46 +69,90 @@ impl Statistics {
     }
 
     pub fn get_level(&self) -> i32 {
-        let modifier = match self.rarity {
-            Rarity::Common => 0,
-            Rarity::Uncommon => 1,
-            Rarity::Rare => 2,
-            Rarity::VeryRare => 3,
-            Rarity::Legendary => 4,
-        };
-
-        self.level + modifier
+        self.level + self.rarity.get_tier()
     }
 }
 
-pub fn new_single_target_damage(prof: Proficiency) -> Stat {
-    let raw_damage: i32 = match prof {
-        Proficiency::Extreme => 8,
-        Proficiency::High => 6,
-        Proficiency::Moderate => 4,
-        Proficiency::Low => 3,
-        Proficiency::Terrible => 2,
+// Dice are picked so that their averages match the fixed damage values
+// that were used before damage was rolled.
+pub fn new_single_target_damage(prof: Proficiency) -> ScaledDice {
+    let raw_damage = match prof {
+        Proficiency::Extreme => Dice::new(2, 6, 1),
+        Proficiency::High => Dice::new(2, 4, 1),
+        Proficiency::Moderate => Dice::new(1, 3, 2),
+        Proficiency::Low => Dice::new(1, 3, 1),
+        Proficiency::Terrible => Dice::new(1, 3, 0),
+    };
+
+    new_damage_dice(raw_damage)
+}
+
+pub fn new_area_damage(prof: Proficiency) -> ScaledDice {
+    let raw_damage = match prof {
+        Proficiency::Extreme => Dice::new(2, 4, 1),
+        Proficiency::High => Dice::new(1, 3, 2),
+        Proficiency::Moderate => Dice::new(1, 3, 1),
+        Proficiency::Low => Dice::new(1, 3, 0),
+        Proficiency::Terrible => Dice::new(1, 3, -1),
     };
 
+    new_damage_dice(raw_damage)
+}
+
+pub fn new_damage_dice(raw_damage: Dice) -> ScaledDice {
     // After leveling up this many times, our damage has doubled.
     let double_after_levels = 2.0;
     let increase_per_level: f64 = f64::powf(2.0, 1.0 / double_after_levels);
 
-    Stat::new(raw_damage, LevelScaling::Exponential(increase_per_level))
+    ScaledDice::new(raw_damage, LevelScaling::Exponential(increase_per_level))
 }
 
-pub fn new_area_damage(prof: Proficiency) -> Stat {
-    let raw_damage: i32 = match prof {
-        Proficiency::Extreme => 6,
-        Proficiency::High => 4,
-        Proficiency::Moderate => 3,
-        Proficiency::Low => 2,
-        Proficiency::Terrible => 1,
+pub fn new_healing(prof: Proficiency) -> Stat {
+    let raw_healing: i32 = match prof {
+        Proficiency::Extreme => 10,
+        Proficiency::High => 8,
+        Proficiency::Moderate => 6,
+        Proficiency::Low => 4,
+        Proficiency::Terrible => 2,
     };
 
-    // After leveling up this many times, our damage has doubled.
+    // Healing keeps up with creature HP, which doubles every two levels.
     let double_after_levels = 2.0;
     let increase_per_level: f64 = f64::powf(2.0, 1.0 / double_after_levels);
 
-    Stat::new(raw_damage, LevelScaling::Exponential(increase_per_level))
+    Stat::new(raw_healing, LevelScaling::Exponential(increase_per_level))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants;
+
+    #[test]
+    fn test_damage_averages() {
+        let proficiencies = [
+            Proficiency::Extreme,
+            Proficiency::High,
+            Proficiency::Moderate,
+            Proficiency::Low,
+            Proficiency::Terrible,
+        ];
+        let single_target = [8.0, 6.0, 4.0, 3.0, 2.0];
+        let area = [6.0, 4.0, 3.0, 2.0, 1.0];
+
+        // The fixed values scaled with level the same way as the dice
+        let scaling = LevelScaling::Exponential(f64::powf(2.0, 0.5));
+
+        for level in constants::MIN_LEVEL..=constants::MAX_LEVEL {
+            for (i, prof) in proficiencies.into_iter().enumerate() {
+                let dice = new_single_target_damage(prof).get_dice(level);
+                let expected = scaling.apply(single_target[i], level);
+                assert!((dice.average() - expected).abs() < 0.5);
+
+                let dice = new_area_damage(prof).get_dice(level);
+                let expected = scaling.apply(area[i], level);
+                assert!((dice.average() - expected).abs() < 0.5);
+            }
+        }
+    }
 }
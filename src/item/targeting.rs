@@ -75,10 +75,53 @@ pub fn get_targeted_squares(
             }
             return Ok(vec![target]);
         }
+        TargetKind::Burst(params) => {
+            let dist = gridalgos::distance(target, actor_pos);
+            if dist > params.range {
+                return Err(TargetingError::OutOfRange);
+            }
+
+            let mut squares: Vec<GridSquare> = Vec::new();
+            for y in (target.y - params.radius)..=(target.y + params.radius) {
+                for x in (target.x - params.radius)..=(target.x + params.radius) {
+                    let square = GridSquare { y, x };
+
+                    if gridalgos::distance(square, target) <= params.radius
+                        && layer.grid.free_square(square)
+                    {
+                        squares.push(square);
+                    }
+                }
+            }
+            Ok(squares)
+        }
         _ => panic!("unimplemented target type"),
     }
 }
 
+// Walls and doors caught in the area of the effect. Burst areas only
+// reach the tiles that are in plain view of their center. Other shapes
+// don't reach any tiles yet.
+pub fn get_targeted_tiles(
+    target: GridSquare,
+    target_kind: TargetKind,
+    layer: &Layer,
+) -> Vec<GridSquare> {
+    let squares = match target_kind {
+        TargetKind::Square(_) => vec![target],
+        TargetKind::Burst(params) => gridalgos::emanation(target, params.radius)
+            .into_iter()
+            .filter(|sq| gridalgos::line_of_sight(&layer.grid, target, *sq))
+            .collect(),
+        _ => Vec::new(),
+    };
+
+    squares
+        .into_iter()
+        .filter(|sq| layer.grid.destructible_square(*sq))
+        .collect()
+}
+
 pub fn get_affected_creatures(targeted_squares: Vec<GridSquare>, layer: &Layer) -> Vec<u128> {
     let mut creatures: Vec<u128> = Vec::new();
 
@@ -89,3 +132,58 @@ pub fn get_affected_creatures(targeted_squares: Vec<GridSquare>, layer: &Layer)
 
     creatures
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::{Behavior, AI};
+    use crate::creature::statistics::Statistics;
+    use crate::creature::Creature;
+    use crate::grid::Tile;
+
+    #[test]
+    fn test_burst() {
+        let mut layer = Layer::new(10, 10);
+        layer.grid.set_tile(GridSquare { y: 5, x: 6 }, Tile::Wall);
+
+        let actor_pos = GridSquare { y: 5, x: 1 };
+        let actor = Creature::new(String::from("actor"), actor_pos, Statistics::get_default());
+        let actor_id = actor.get_id();
+        let c_ai = AI::new(actor_id, Behavior::Inactive, Behavior::Inactive);
+        layer.add_creature(actor, c_ai);
+
+        let burst = TargetKind::Burst(BurstParams {
+            range: 4,
+            radius: 1,
+        });
+
+        let target = GridSquare { y: 5, x: 5 };
+        let squares = get_targeted_squares(target, burst, actor_id, &layer).unwrap();
+
+        // 3x3 square around the target, except for the wall
+        assert_eq!(squares.len(), 8);
+        assert!(squares.contains(&target));
+        assert!(!squares.contains(&GridSquare { y: 5, x: 6 }));
+
+        let too_far = GridSquare { y: 5, x: 6 };
+        assert!(get_targeted_squares(too_far, burst, actor_id, &layer).is_err());
+    }
+
+    #[test]
+    fn test_targeted_tiles() {
+        let mut layer = Layer::new(10, 10);
+        let wall = GridSquare { y: 5, x: 6 };
+        layer.grid.set_tile(wall, Tile::Wall);
+
+        let target = GridSquare { y: 5, x: 5 };
+        let burst = TargetKind::Burst(BurstParams {
+            range: 4,
+            radius: 1,
+        });
+        assert_eq!(get_targeted_tiles(target, burst, &layer), vec![wall]);
+
+        // Shapes that can't reach tiles yet don't break anything
+        let cone = TargetKind::Cone(ConeParams { length: 3 });
+        assert!(get_targeted_tiles(target, cone, &layer).is_empty());
+    }
+}
@@ -2,6 +2,9 @@ pub mod ai;
 pub mod check;
 pub mod constants;
 pub mod creature;
+pub mod damage;
+pub mod data;
+pub mod dice;
 pub mod grid;
 pub mod info_message;
 pub mod initiative;
@@ -10,4 +13,7 @@ pub mod messages;
 pub mod net;
 pub mod player;
 pub mod stat;
+pub mod trap;
+pub mod vault;
 pub mod world;
+pub mod worldgen;
@@ -1,8 +1,11 @@
 use crate::creature::action::Action;
+use crate::creature::character::CharacterOptions;
+use crate::creature::reaction::Reaction;
 use crate::creature::Creature;
-use crate::grid::Grid;
+use crate::grid::{Grid, GridSquare};
 use crate::info_message::MessageType;
 use crate::item::Item;
+use crate::trap::Trap;
 use crate::world::Layer;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -32,6 +35,10 @@ pub enum Message {
     ActionOk,
     ActionError,
     TurnStart(TurnStartMsg),
+    ActionsLeft(ActionsLeftMsg),
+    ReactionPrompt(ReactionPromptMsg),
+    React(ReactMsg),
+    GameOver(GameOverMsg),
 }
 
 impl fmt::Display for Message {
@@ -46,6 +53,10 @@ impl fmt::Display for Message {
             Message::ActionOk => "ActionOk",
             Message::ActionError => "ActionError",
             Message::TurnStart(_) => "TurnStart",
+            Message::ActionsLeft(_) => "ActionsLeft",
+            Message::ReactionPrompt(_) => "ReactionPrompt",
+            Message::React(_) => "React",
+            Message::GameOver(_) => "GameOver",
         };
 
         write!(f, "{}", variant)
@@ -56,6 +67,7 @@ impl fmt::Display for Message {
 pub struct JoinMsg {
     pub version: String,
     pub character_name: String,
+    pub character: CharacterOptions,
 }
 
 #[derive(Deserialize, Serialize)]
@@ -73,6 +85,34 @@ pub struct TurnStartMsg {
     pub acting_creature: u128,
 }
 
+#[derive(Deserialize, Serialize)]
+pub struct ActionsLeftMsg {
+    pub acting_creature: u128,
+    pub actions_left: i32,
+    pub max_actions: i32,
+}
+
+// Offers a reaction to the player controlling the reacting creature.
+// Not answering before the timeout declines the reaction.
+#[derive(Deserialize, Serialize)]
+pub struct ReactionPromptMsg {
+    pub reaction: Reaction,
+    pub description: String,
+    pub timeout_ms: u64,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct ReactMsg {
+    pub accept: bool,
+}
+
+// Sent to every player when the game ends. The server closes the
+// connections afterwards.
+#[derive(Deserialize, Serialize)]
+pub struct GameOverMsg {
+    pub message: String,
+}
+
 #[derive(Deserialize, Serialize)]
 pub enum CreatureOwner {
     Player(u128),
@@ -84,6 +124,9 @@ pub struct GameStateMsg {
     pub creatures: HashMap<u128, Creature>,
     pub creature_owners: HashMap<u128, CreatureOwner>,
     pub items: HashMap<u128, Item>,
+    pub item_positions: HashMap<u128, GridSquare>,
+    // Only the traps that have been found
+    pub traps: HashMap<u128, Trap>,
     pub grid: Grid,
 }
 
@@ -111,6 +154,13 @@ impl GameStateMsg {
             creatures: layer.creatures.clone(),
             creature_owners,
             items: layer.items.clone(),
+            item_positions: layer.item_positions.positions().clone(),
+            traps: layer
+                .traps
+                .iter()
+                .filter(|(_, t)| !t.hidden)
+                .map(|(id, t)| (*id, t.clone()))
+                .collect(),
             grid: layer.grid.clone(),
         }
     }
@@ -2,6 +2,7 @@ use crate::messages::{Header, Message};
 use std::io::{prelude::*, ErrorKind};
 use std::mem;
 use std::net::TcpStream;
+use std::time::Duration;
 
 fn create_header(data: &Vec<u8>) -> Vec<u8> {
     let header = Header::new(data.len());
@@ -89,3 +90,34 @@ pub fn receive(stream: &mut TcpStream) -> Message {
 
     data
 }
+
+// Returns None if no message starts arriving before the timeout. Once the
+// first byte is available, the rest of the message is read without a timeout.
+pub fn receive_timeout(stream: &mut TcpStream, timeout: Duration) -> Option<Message> {
+    if let Err(e) = stream.set_read_timeout(Some(timeout)) {
+        panic!("failed to set read timeout: {:?}", e);
+    }
+
+    let mut buffer = [0u8; 1];
+    let ready = loop {
+        match stream.peek(&mut buffer) {
+            Ok(0) => panic!("connection has been shut down"),
+            Ok(_) => break true,
+            Err(e) => match e.kind() {
+                ErrorKind::Interrupted => continue,
+                ErrorKind::WouldBlock | ErrorKind::TimedOut => break false,
+                _ => panic!("failed to receive bytes: {:?}", e),
+            },
+        }
+    };
+
+    if let Err(e) = stream.set_read_timeout(None) {
+        panic!("failed to clear read timeout: {:?}", e);
+    }
+
+    if !ready {
+        return None;
+    }
+
+    Some(receive(stream))
+}
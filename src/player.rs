@@ -1,23 +1,38 @@
+use crate::creature::character::CharacterOptions;
 use rand::prelude::*;
 use std::net::TcpStream;
 
 pub struct Player {
     pub socket: TcpStream,
+    pub character_name: String,
+    pub character: CharacterOptions,
     id: u128,
 }
 
 impl Player {
-    pub fn new(socket: TcpStream) -> Self {
+    pub fn new(socket: TcpStream, character_name: String, character: CharacterOptions) -> Self {
         let mut rng = rand::thread_rng();
 
         Self {
             socket: socket,
+            character_name,
+            character,
             id: rng.gen(),
         }
     }
 
-    pub fn build_existing(socket: TcpStream, id: u128) -> Self {
-        Self { socket, id }
+    pub fn build_existing(
+        socket: TcpStream,
+        id: u128,
+        character_name: String,
+        character: CharacterOptions,
+    ) -> Self {
+        Self {
+            socket,
+            character_name,
+            character,
+            id,
+        }
     }
 
     pub fn get_id(&self) -> u128 {
@@ -9,7 +9,30 @@ pub enum LevelScaling {
     Exponential(f64),
 }
 
-#[derive(Clone, Deserialize, Serialize)]
+impl LevelScaling {
+    pub fn apply(&self, val: f64, level: i32) -> f64 {
+        let mut scaled_val = val;
+
+        match self {
+            LevelScaling::Linear(increment) => {
+                scaled_val += (level * increment) as f64;
+            }
+            LevelScaling::Exponential(base) => {
+                // Our level scale starts from constants::MIN_LEVEL instead of 1,
+                // so shift levels so that MIN_LEVEL is mapped to level 1.
+                let shift: i32 = 1 - constants::MIN_LEVEL;
+                let shifted_level = level + shift;
+
+                scaled_val *= base.powi(shifted_level);
+            }
+            LevelScaling::NoScaling => (),
+        }
+
+        scaled_val
+    }
+}
+
+#[derive(Clone, Copy, Deserialize, Serialize)]
 pub enum Proficiency {
     Extreme,
     High,
@@ -59,24 +82,7 @@ impl Stat {
     }
 
     fn scale_by_level(&self, val: f64, level: i32) -> f64 {
-        let mut scaled_val = val;
-
-        match self.level_scaling {
-            LevelScaling::Linear(increment) => {
-                scaled_val += (level * increment) as f64;
-            }
-            LevelScaling::Exponential(base) => {
-                // Our level scale starts from constants::MIN_LEVEL instead of 1,
-                // so shift levels so that MIN_LEVEL is mapped to level 1.
-                let shift: i32 = 1 - constants::MIN_LEVEL;
-                let shifted_level = level + shift;
-
-                scaled_val *= base.powi(shifted_level);
-            }
-            LevelScaling::NoScaling => (),
-        }
-
-        scaled_val
+        self.level_scaling.apply(val, level)
     }
 
     pub fn get_total_additive_modifier(&self) -> i32 {
@@ -0,0 +1,368 @@
+use crate::check::{self, Outcome};
+use crate::creature::statistics::{self as creature_statistics, CreatureStat};
+use crate::damage::{self, DamageType};
+use crate::grid::gridalgos;
+use crate::grid::GridSquare;
+use crate::info_message::MessageType;
+use crate::item::effect::{ApplyFn, Duration, Effect, EffectResult, OngoingEffect};
+use crate::item::statistics::{self as item_statistics, Rarity, Statistics};
+use crate::stat::Proficiency;
+use crate::world::Layer;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+// Rounds a snared creature is unable to move, including the one it is
+// caught in
+const SNARE_DURATION: i32 = 2;
+
+#[derive(Clone, Copy, Deserialize, PartialEq, Serialize)]
+pub enum TrapKind {
+    Spikes,
+    PoisonDart,
+    Glyph,
+    Snare,
+}
+
+impl TrapKind {
+    pub fn all() -> Vec<TrapKind> {
+        vec![
+            TrapKind::Spikes,
+            TrapKind::PoisonDart,
+            TrapKind::Glyph,
+            TrapKind::Snare,
+        ]
+    }
+
+    // The saving throw the trap is rolled against
+    pub fn get_save(&self) -> CreatureStat {
+        match self {
+            TrapKind::Spikes | TrapKind::Snare => CreatureStat::ReflexDc,
+            TrapKind::PoisonDart => CreatureStat::FortitudeDc,
+            TrapKind::Glyph => CreatureStat::WillDc,
+        }
+    }
+
+    fn get_damage_type(&self) -> Option<DamageType> {
+        match self {
+            TrapKind::Spikes => Some(DamageType::Piercing),
+            TrapKind::PoisonDart => Some(DamageType::Poison),
+            TrapKind::Glyph => Some(DamageType::Fire),
+            TrapKind::Snare => None,
+        }
+    }
+
+    fn get_apply(&self) -> ApplyFn {
+        match self {
+            TrapKind::Snare => snare,
+            _ => damage_trap,
+        }
+    }
+}
+
+impl fmt::Display for TrapKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            TrapKind::Spikes => "spike trap",
+            TrapKind::PoisonDart => "poison dart trap",
+            TrapKind::Glyph => "glyph of warding",
+            TrapKind::Snare => "snare",
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+// Traps trigger once when a creature steps on them. Like items, a trap
+// and its effect share the same id.
+#[derive(Clone, Deserialize, Serialize)]
+pub struct Trap {
+    pub kind: TrapKind,
+    pub level: i32,
+    pub position: GridSquare,
+    // Hidden traps are not sent to the clients
+    pub hidden: bool,
+    // Creatures that have already tried to spot the trap
+    searched_by: Vec<u128>,
+
+    id: u128,
+}
+
+impl Trap {
+    pub fn new(kind: TrapKind, level: i32, position: GridSquare) -> (Self, Effect) {
+        let mut stats = Statistics::new(level, Rarity::Common);
+        stats.dice.insert(
+            String::from("damage"),
+            item_statistics::new_single_target_damage(Proficiency::High),
+        );
+        stats.damage_type = kind.get_damage_type();
+
+        let mut effect = Effect::new(Duration::Instantaneous, stats, kind.get_apply(), None, None);
+
+        if kind == TrapKind::Snare {
+            effect.duration = Duration::Temporary(SNARE_DURATION);
+            effect.remove = Some(release_snare);
+        }
+
+        let trap = Self {
+            kind,
+            level,
+            position,
+            hidden: true,
+            searched_by: Vec::new(),
+            id: effect.get_id(),
+        };
+
+        (trap, effect)
+    }
+
+    pub fn get_id(&self) -> u128 {
+        self.id
+    }
+
+    pub fn get_attack_bonus(&self) -> i32 {
+        creature_statistics::new_attack(Proficiency::Moderate).get_value(self.level)
+    }
+
+    // DC of the perception check for noticing the trap
+    pub fn get_stealth_dc(&self) -> i32 {
+        creature_statistics::new_defense(Proficiency::Moderate).get_value(self.level)
+    }
+
+    pub fn get_disable_dc(&self) -> i32 {
+        creature_statistics::new_defense(Proficiency::High).get_value(self.level)
+    }
+}
+
+// Player characters get one perception check against each hidden trap
+// next to them. Initiative is rolled with perception, so the same
+// modifier is used here. Returns the traps that were noticed.
+pub fn search(searcher_id: u128, layer: &mut Layer) -> Vec<u128> {
+    let is_player = layer
+        .creature_ai
+        .get(&searcher_id)
+        .is_some_and(|ai| ai.is_player_controlled());
+    if !is_player {
+        return Vec::new();
+    }
+
+    let searcher = layer
+        .creatures
+        .get(&searcher_id)
+        .expect("searcher should exist");
+    let position = searcher.get_position();
+    let perception = searcher.stats.initiative.get_value(searcher.stats.level);
+
+    let mut noticed: Vec<u128> = Vec::new();
+
+    for trap in layer.traps.values_mut() {
+        if !trap.hidden
+            || trap.searched_by.contains(&searcher_id)
+            || gridalgos::distance(trap.position, position) > 1
+        {
+            continue;
+        }
+
+        trap.searched_by.push(searcher_id);
+
+        let check = check::skill_check(perception, trap.get_stealth_dc());
+        if matches!(check.outcome, Outcome::Success | Outcome::CriticalSuccess) {
+            trap.hidden = false;
+            noticed.push(trap.id);
+        }
+    }
+
+    noticed
+}
+
+// Sets the trap off against the creatures at the target square. The trap
+// is used up, but its effect is kept so that ongoing effects can end.
+pub fn trigger(trap_id: u128, target: GridSquare, layer: &mut Layer) -> MessageType {
+    let effect = layer
+        .effects
+        .get(&trap_id)
+        .expect("a trap should have an effect");
+    let effect_result = (effect.apply)(trap_id, trap_id, target, layer);
+
+    if let Some(e) = effect_result.ongoing_effect {
+        layer.ongoing_effects.insert(e.get_id(), e);
+    }
+
+    layer.traps.remove(&trap_id);
+
+    effect_result.message
+}
+
+// Disabling a trap is a check against its disable DC. Critically failing
+// sets the trap off.
+pub fn disarm(disarmer_id: u128, trap_id: u128, layer: &mut Layer) -> MessageType {
+    let disarmer = layer
+        .creatures
+        .get(&disarmer_id)
+        .expect("disarmer should exist");
+    let trap = layer.traps.get(&trap_id).expect("trap should exist");
+
+    // DCs are 10 higher than the corresponding modifiers
+    let modifier = disarmer.stats.reflex_dc.get_value(disarmer.stats.level) - 10;
+    let check = check::skill_check(modifier, trap.get_disable_dc());
+    let summary = format!(
+        "{} tries to disarm the {} ({})",
+        disarmer.name,
+        trap.kind,
+        check::describe(&check)
+    );
+
+    match check.outcome {
+        Outcome::CriticalSuccess | Outcome::Success => {
+            layer.traps.remove(&trap_id);
+            MessageType::Info(format!("{} and succeeds", summary))
+        }
+        Outcome::Failure => MessageType::Info(format!("{} but fails", summary)),
+        Outcome::CriticalFailure => {
+            let position = disarmer.get_position();
+            let result = trigger(trap_id, position, layer);
+
+            let result = match result {
+                MessageType::Info(msg) => msg,
+                _ => String::new(),
+            };
+
+            MessageType::Info(format!("{} and sets it off. {}", summary, result))
+        }
+    }
+}
+
+fn get_trap(trap_id: u128, layer: &Layer) -> &Trap {
+    layer.traps.get(&trap_id).expect("trap should exist")
+}
+
+// Effect of traps that deal damage. The owner of a trap effect is the trap.
+pub fn damage_trap(
+    effect_id: u128,
+    trap_id: u128,
+    target: GridSquare,
+    layer: &mut Layer,
+) -> EffectResult {
+    let effect = layer.effects.get(&effect_id).unwrap();
+    let trap_damage = effect.get_dice("damage");
+    let damage_type = effect
+        .stats
+        .damage_type
+        .expect("damaging traps should have a damage type");
+
+    let trap = get_trap(trap_id, layer);
+    let kind = trap.kind;
+    let attack_bonus = trap.get_attack_bonus();
+
+    let mut results: Vec<String> = Vec::new();
+    let mut rng = rand::thread_rng();
+
+    for c in layer.get_living_creatures_at(target) {
+        let creature = layer.creatures.get_mut(&c).unwrap();
+        let check = check::save_roll(attack_bonus, &creature.stats, kind.get_save());
+
+        let damage_multiplier = match check.outcome {
+            Outcome::CriticalSuccess => 2,
+            Outcome::Success => 1,
+            _ => 0,
+        };
+        // Damage is only rolled when the trap gets through
+        let roll = (damage_multiplier > 0).then(|| trap_damage.roll(&mut rng));
+        let rolled_damage = roll.as_ref().map_or(0, |r| r.total);
+
+        let damage = damage::calculate(
+            rolled_damage * damage_multiplier,
+            damage_type,
+            &creature.stats,
+        );
+        creature.change_hp(-damage.total);
+
+        let result = match roll {
+            Some(roll) if damage.base > 0 => format!("takes {} ({})", damage, roll),
+            _ => String::from("avoids it"),
+        };
+
+        results.push(format!(
+            "{} sets off a {} ({}) and {}",
+            creature.name,
+            kind,
+            check::describe(&check),
+            result
+        ));
+    }
+
+    EffectResult {
+        ongoing_effect: None,
+        message: MessageType::Info(results.join(". ")),
+    }
+}
+
+// Holds the creatures in place until the effect runs out
+pub fn snare(
+    effect_id: u128,
+    trap_id: u128,
+    target: GridSquare,
+    layer: &mut Layer,
+) -> EffectResult {
+    let trap = get_trap(trap_id, layer);
+    let kind = trap.kind;
+    let attack_bonus = trap.get_attack_bonus();
+
+    let mut results: Vec<String> = Vec::new();
+    let mut caught = false;
+
+    for c in layer.get_living_creatures_at(target) {
+        let creature = layer.creatures.get_mut(&c).unwrap();
+        let check = check::save_roll(attack_bonus, &creature.stats, kind.get_save());
+
+        let result = match check.outcome {
+            Outcome::CriticalSuccess | Outcome::Success => {
+                creature
+                    .stats
+                    .movement_speed
+                    .apply_multiplicative_modifier(effect_id, 0.0);
+                caught = true;
+                "is caught"
+            }
+            _ => "slips free",
+        };
+
+        results.push(format!(
+            "{} sets off a {} ({}) and {}",
+            creature.name,
+            kind,
+            check::describe(&check),
+            result
+        ));
+    }
+
+    let ongoing_effect = if caught {
+        let duration = Duration::Temporary(SNARE_DURATION);
+        Some(OngoingEffect::new(effect_id, trap_id, target, duration))
+    } else {
+        None
+    };
+
+    EffectResult {
+        ongoing_effect,
+        message: MessageType::Info(results.join(". ")),
+    }
+}
+
+// Snared creatures can't move, so they are still at the target square
+pub fn release_snare(
+    effect_id: u128,
+    _trap_id: u128,
+    target: GridSquare,
+    layer: &mut Layer,
+) -> MessageType {
+    let mut released: Vec<String> = Vec::new();
+
+    for c in layer.get_living_creatures_at(target) {
+        let creature = layer.creatures.get_mut(&c).unwrap();
+        creature.stats.movement_speed.remove_modifier(effect_id);
+
+        released.push(format!("{} breaks free of the snare", creature.name));
+    }
+
+    MessageType::Info(released.join(". "))
+}
@@ -0,0 +1,313 @@
+use crate::constants;
+use crate::creature::bestiary::Bestiary;
+use crate::creature::encounter;
+use crate::data::{self, DataError};
+use crate::grid::{Grid, GridSquare, Tile};
+use crate::item::catalog::ItemCatalog;
+use crate::item::generator;
+use crate::item::statistics::Rarity;
+use crate::world::Layer;
+use rand::prelude::*;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::Path;
+
+pub const VAULTS_FILE: &str = "vaults.ron";
+
+// What a legend character in a vault map stands for. The square under a
+// creature or an item is empty floor.
+#[derive(Clone, Deserialize)]
+pub enum LegendEntry {
+    // Creature from the bestiary
+    Creature(String),
+    // Item from the catalog, lying on the floor
+    Item(String, Rarity),
+}
+
+#[derive(Deserialize)]
+pub struct VaultDefinition {
+    pub name: String,
+    // Layer depths the vault can appear at
+    pub depths: Vec<i32>,
+    // Rows of tiles in the same characters that Grid uses for display,
+    // mixed with the characters of the legend
+    pub map: String,
+    #[serde(default)]
+    pub legend: HashMap<char, LegendEntry>,
+}
+
+// Hand-made set piece that world generation stamps into a layer
+pub struct Vault {
+    pub name: String,
+    pub depths: Vec<i32>,
+    pub grid: Grid,
+    // Squares are relative to the upper left corner of the vault
+    pub contents: Vec<(GridSquare, LegendEntry)>,
+}
+
+impl Vault {
+    fn from_definition(
+        definition: VaultDefinition,
+        bestiary: &Bestiary,
+        catalog: &ItemCatalog,
+    ) -> Result<Self, DataError> {
+        let name = definition.name;
+        let invalid = |msg: String| DataError::Invalid(format!("{}: {}", name, msg));
+
+        if name.is_empty() {
+            return Err(DataError::Invalid(String::from(
+                "vault definition has no name",
+            )));
+        }
+
+        for depth in &definition.depths {
+            if !(0..constants::WORLD_LAYERS).contains(depth) {
+                return Err(invalid(format!("there is no layer at depth {}", depth)));
+            }
+        }
+
+        for (c, entry) in &definition.legend {
+            if Tile::from_char(*c).is_some() {
+                return Err(invalid(format!("legend character '{}' is a tile", c)));
+            }
+
+            match entry {
+                LegendEntry::Creature(creature) if bestiary.get(creature).is_none() => {
+                    return Err(invalid(format!("unknown creature \"{}\"", creature)));
+                }
+                LegendEntry::Item(item, rarity)
+                    if catalog.create_item(item, 0, *rarity).is_none() =>
+                {
+                    return Err(invalid(format!(
+                        "unknown item \"{}\" or the item can't have that rarity",
+                        item
+                    )));
+                }
+                _ => (),
+            }
+        }
+
+        // Legend characters are read as floor for the grid itself
+        let mut contents: Vec<(GridSquare, LegendEntry)> = Vec::new();
+        let mut map = String::new();
+
+        for (y, line) in definition.map.lines().enumerate() {
+            for (x, c) in line.chars().enumerate() {
+                match definition.legend.get(&c) {
+                    Some(entry) => {
+                        let square = GridSquare {
+                            y: y as i32,
+                            x: x as i32,
+                        };
+                        contents.push((square, entry.clone()));
+                        map.push(Tile::Empty.to_char());
+                    }
+                    None => map.push(c),
+                }
+            }
+            map.push('\n');
+        }
+
+        // Leading empty lines are skipped when the grid is parsed
+        let skipped = definition.map.lines().take_while(|l| l.is_empty()).count() as i32;
+        for (square, _) in &mut contents {
+            square.y -= skipped;
+        }
+
+        let grid = map.parse::<Grid>().map_err(invalid)?;
+
+        Ok(Self {
+            name,
+            depths: definition.depths,
+            grid,
+            contents,
+        })
+    }
+
+    pub fn height(&self) -> i32 {
+        self.grid.height()
+    }
+
+    pub fn width(&self) -> i32 {
+        self.grid.width()
+    }
+
+    // Overwrites the tiles under the vault and adds its creatures and
+    // items. Creatures are as close to the expected party level of the
+    // layer as their level range allows.
+    pub fn stamp<R: Rng>(
+        &self,
+        top_left: GridSquare,
+        depth: i32,
+        layer: &mut Layer,
+        bestiary: &Bestiary,
+        catalog: &ItemCatalog,
+        rng: &mut R,
+    ) {
+        let to_layer = |square: GridSquare| GridSquare {
+            y: top_left.y + square.y,
+            x: top_left.x + square.x,
+        };
+
+        for y in 0..self.height() {
+            for x in 0..self.width() {
+                let square = GridSquare { y, x };
+                let tile = self.grid.get_tile(square).unwrap();
+                layer.grid.set_tile(to_layer(square), tile);
+            }
+        }
+
+        for (square, entry) in &self.contents {
+            let position = to_layer(*square);
+
+            match entry {
+                LegendEntry::Creature(name) => {
+                    let definition = bestiary
+                        .get(name)
+                        .expect("vaults should have been validated");
+                    let (min_level, max_level) = definition.level_range;
+                    let level = encounter::get_party_level(depth).clamp(min_level, max_level);
+
+                    definition.spawn(level, position, catalog, layer, rng);
+                }
+                LegendEntry::Item(name, rarity) => {
+                    let definition = catalog
+                        .get(name)
+                        .expect("vaults should have been validated");
+                    let (item, effect) = generator::generate(definition, depth, *rarity, rng);
+
+                    let item_id = item.get_id();
+                    layer.add_item(item, effect);
+                    layer.place_item(item_id, position);
+                }
+            }
+        }
+    }
+}
+
+pub struct Vaults {
+    vaults: Vec<Vault>,
+}
+
+impl Vaults {
+    // Creatures and items are checked against the bestiary and catalog
+    pub fn load(
+        path: &Path,
+        bestiary: &Bestiary,
+        catalog: &ItemCatalog,
+    ) -> Result<Self, DataError> {
+        let definitions: Vec<VaultDefinition> = data::load(path)?;
+        Self::build(definitions, bestiary, catalog)
+    }
+
+    pub fn load_default(bestiary: &Bestiary, catalog: &ItemCatalog) -> Result<Self, DataError> {
+        let path = Path::new(constants::DATA_DIRECTORY).join(VAULTS_FILE);
+        Self::load(&path, bestiary, catalog)
+    }
+
+    pub fn parse(
+        vaults: &str,
+        bestiary: &Bestiary,
+        catalog: &ItemCatalog,
+    ) -> Result<Self, DataError> {
+        let definitions: Vec<VaultDefinition> = data::parse(vaults)?;
+        Self::build(definitions, bestiary, catalog)
+    }
+
+    fn build(
+        definitions: Vec<VaultDefinition>,
+        bestiary: &Bestiary,
+        catalog: &ItemCatalog,
+    ) -> Result<Self, DataError> {
+        let mut vaults: Vec<Vault> = Vec::new();
+
+        for definition in definitions {
+            let vault = Vault::from_definition(definition, bestiary, catalog)?;
+
+            if vaults.iter().any(|v| v.name == vault.name) {
+                return Err(DataError::Invalid(format!(
+                    "vault \"{}\" is defined more than once",
+                    vault.name
+                )));
+            }
+
+            vaults.push(vault);
+        }
+
+        Ok(Self { vaults })
+    }
+
+    pub fn get_all(&self) -> &Vec<Vault> {
+        &self.vaults
+    }
+
+    // Random vault that can appear at the depth
+    pub fn choose<R: Rng>(&self, depth: i32, rng: &mut R) -> Option<&Vault> {
+        let candidates: Vec<&Vault> = self
+            .vaults
+            .iter()
+            .filter(|v| v.depths.contains(&depth))
+            .collect();
+
+        candidates.choose(rng).copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BESTIARY: &str = include_str!("../data/bestiary.ron");
+    const CATALOG: &str = include_str!("../data/items.ron");
+    const VAULTS: &str = include_str!("../data/vaults.ron");
+
+    fn parse(vaults: &str) -> Result<Vaults, DataError> {
+        let catalog = ItemCatalog::parse(CATALOG).unwrap();
+        let bestiary = Bestiary::parse(BESTIARY, &catalog).unwrap();
+        Vaults::parse(vaults, &bestiary, &catalog)
+    }
+
+    #[test]
+    fn test_vaults_are_valid() {
+        let vaults = parse(VAULTS);
+        assert!(vaults.is_ok(), "{}", vaults.err().unwrap());
+    }
+
+    #[test]
+    fn test_map_parsing() {
+        let vaults = parse(
+            r#"[(
+                name: "Closet",
+                depths: [0],
+                map: "
+#####
+#g.>#
+##+##",
+                legend: {'g': Creature("Goblin")},
+            )]"#,
+        )
+        .unwrap();
+
+        let vault = &vaults.get_all()[0];
+        assert_eq!((vault.height(), vault.width()), (3, 5));
+        assert_eq!(vault.grid.to_string(), "#####\n#..>#\n##+##\n");
+        assert!(matches!(
+            vault.contents[..],
+            [(GridSquare { y: 1, x: 1 }, LegendEntry::Creature(_))]
+        ));
+
+        let ragged = r#"[(name: "Closet", depths: [0], map: "..\n.")]"#;
+        assert!(matches!(parse(ragged), Err(DataError::Invalid(_))));
+
+        let unknown_creature = r#"[(
+            name: "Closet",
+            depths: [0],
+            map: ".g.",
+            legend: {'g': Creature("Banana")},
+        )]"#;
+        assert!(matches!(
+            parse(unknown_creature),
+            Err(DataError::Invalid(_))
+        ));
+    }
+}
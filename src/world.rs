@@ -3,9 +3,13 @@ use crate::creature::Creature;
 use crate::grid::{Grid, GridSquare};
 use crate::item::effect::{Effect, OngoingEffect};
 use crate::item::Item;
+use crate::trap::Trap;
+use spatial_index::SpatialIndex;
 use std::collections::HashMap;
 use std::fmt;
 
+pub mod spatial_index;
+
 pub struct World {
     pub layers: Vec<Layer>,
 }
@@ -17,12 +21,21 @@ impl World {
 }
 
 pub struct Layer {
+    // Creatures are added with add_creature and moved with move_creature,
+    // which keep their positions in the index
     pub creatures: HashMap<u128, Creature>,
     pub creature_ai: HashMap<u128, AI>,
+    pub creature_positions: SpatialIndex,
     pub grid: Grid,
     pub items: HashMap<u128, Item>,
+    // Positions of the items that lie on the floor instead of in an inventory
+    pub item_positions: SpatialIndex,
     pub effects: HashMap<u128, Effect>,
     pub ongoing_effects: HashMap<u128, OngoingEffect>,
+    pub traps: HashMap<u128, Trap>,
+    // Corners of the room that holds the vault, if the layer has one.
+    // Traps, loot and player characters are not placed in it.
+    pub vault_area: Option<(GridSquare, GridSquare)>,
 }
 
 impl Layer {
@@ -35,9 +48,13 @@ impl Layer {
             grid: Grid::new(height, width),
             creatures: HashMap::new(),
             creature_ai: HashMap::new(),
+            creature_positions: SpatialIndex::new(height, width),
             items: HashMap::new(),
+            item_positions: SpatialIndex::new(height, width),
             effects: HashMap::new(),
             ongoing_effects: HashMap::new(),
+            traps: HashMap::new(),
+            vault_area: None,
         }
     }
 
@@ -45,24 +62,51 @@ impl Layer {
         grid: Grid,
         creatures: HashMap<u128, Creature>,
         items: HashMap<u128, Item>,
+        item_positions: HashMap<u128, GridSquare>,
+        traps: HashMap<u128, Trap>,
     ) -> Self {
+        let mut creature_index = SpatialIndex::new(grid.height(), grid.width());
+        for (c_id, creature) in &creatures {
+            creature_index.insert(*c_id, creature.get_position());
+        }
+
+        let mut item_index = SpatialIndex::new(grid.height(), grid.width());
+        for (i_id, square) in item_positions {
+            item_index.insert(i_id, square);
+        }
+
         Self {
             grid: grid,
             creatures: creatures,
+            creature_positions: creature_index,
             items: items,
+            item_positions: item_index,
+            traps,
             creature_ai: HashMap::new(),
             effects: HashMap::new(),
             ongoing_effects: HashMap::new(),
+            vault_area: None,
         }
     }
 
     pub fn add_creature(&mut self, creature: Creature, c_ai: AI) {
         let id = creature.get_id();
 
+        self.creature_positions.insert(id, creature.get_position());
         self.creatures.insert(id, creature);
         self.creature_ai.insert(id, c_ai);
     }
 
+    pub fn move_creature(&mut self, c_id: u128, square: GridSquare) {
+        let creature = self
+            .creatures
+            .get_mut(&c_id)
+            .expect("creature should exist");
+
+        creature.set_position(&square);
+        self.creature_positions.insert(c_id, square);
+    }
+
     pub fn add_item(&mut self, item: Item, effect: Effect) {
         let id = item.get_id();
 
@@ -70,11 +114,37 @@ impl Layer {
         self.effects.insert(id, effect);
     }
 
-    pub fn get_living_creatures_at(&self, square: GridSquare) -> Vec<u128> {
+    pub fn add_trap(&mut self, trap: Trap, effect: Effect) {
+        self.effects.insert(trap.get_id(), effect);
+        self.traps.insert(trap.get_id(), trap);
+    }
+
+    pub fn get_trap_at(&self, square: GridSquare) -> Option<u128> {
+        self.traps
+            .values()
+            .find(|t| t.position == square)
+            .map(|t| t.get_id())
+    }
+
+    pub fn place_item(&mut self, item_id: u128, square: GridSquare) {
+        self.item_positions.insert(item_id, square);
+    }
+
+    // Returns the square the item was lying on, if it was on the floor.
+    pub fn take_item(&mut self, item_id: u128) -> Option<GridSquare> {
+        self.item_positions.remove(item_id)
+    }
+
+    // Sorted by id, so the order stays the same between game state updates
+    pub fn get_items_at(&self, square: GridSquare) -> Vec<u128> {
+        self.item_positions.get_at(square).to_vec()
+    }
+
+    pub fn get_living_creatures(&self) -> Vec<u128> {
         let mut creatures: Vec<u128> = Vec::new();
 
         for (c_id, creature) in &self.creatures {
-            if creature.is_alive() && creature.get_position() == square {
+            if creature.is_alive() {
                 creatures.push(*c_id);
             }
         }
@@ -82,16 +152,43 @@ impl Layer {
         creatures
     }
 
+    fn filter_creatures_at(
+        &self,
+        square: GridSquare,
+        condition: fn(&Creature) -> bool,
+    ) -> Vec<u128> {
+        self.creature_positions
+            .get_at(square)
+            .iter()
+            .filter(|c_id| condition(&self.creatures[c_id]))
+            .copied()
+            .collect()
+    }
+
+    pub fn get_living_creatures_at(&self, square: GridSquare) -> Vec<u128> {
+        self.filter_creatures_at(square, Creature::is_alive)
+    }
+
+    pub fn get_downed_creatures_at(&self, square: GridSquare) -> Vec<u128> {
+        self.filter_creatures_at(square, Creature::is_downed)
+    }
+
     pub fn get_dead_creatures_at(&self, square: GridSquare) -> Vec<u128> {
-        let mut creatures: Vec<u128> = Vec::new();
+        self.filter_creatures_at(square, Creature::is_dead)
+    }
 
-        for (c_id, creature) in &self.creatures {
-            if !creature.is_alive() && creature.get_position() == square {
-                creatures.push(*c_id);
-            }
-        }
+    // Living, downed and dead creatures alike
+    pub fn get_creatures_in_radius(&self, center: GridSquare, radius: i32) -> Vec<u128> {
+        self.creature_positions.get_in_radius(center, radius)
+    }
 
-        creatures
+    pub fn get_creatures_in_rectangle(
+        &self,
+        top_left: GridSquare,
+        bottom_right: GridSquare,
+    ) -> Vec<u128> {
+        self.creature_positions
+            .get_in_rectangle(top_left, bottom_right)
     }
 }
 
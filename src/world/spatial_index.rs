@@ -0,0 +1,130 @@
+use crate::grid::gridalgos;
+use crate::grid::GridSquare;
+use std::collections::HashMap;
+
+// Ids of the things on each square of a layer, so that finding what is in
+// an area doesn't require going through everything on the layer. Only the
+// layer changes the index, to keep it in sync with the positions.
+pub struct SpatialIndex {
+    height: i32,
+    width: i32,
+    // Sorted ids on each square, indexed by y * width + x
+    squares: Vec<Vec<u128>>,
+    positions: HashMap<u128, GridSquare>,
+}
+
+impl SpatialIndex {
+    pub fn new(height: i32, width: i32) -> Self {
+        Self {
+            height,
+            width,
+            squares: vec![Vec::new(); (height * width) as usize],
+            positions: HashMap::new(),
+        }
+    }
+
+    fn index_of(&self, square: GridSquare) -> Option<usize> {
+        let inside = (0..self.height).contains(&square.y) && (0..self.width).contains(&square.x);
+        inside.then(|| (square.y * self.width + square.x) as usize)
+    }
+
+    // Moves the id if it is already in the index
+    pub(super) fn insert(&mut self, id: u128, square: GridSquare) {
+        let i = self
+            .index_of(square)
+            .expect("the square should be inside the layer");
+
+        self.remove(id);
+
+        let ids = &mut self.squares[i];
+        let at = ids.partition_point(|other| *other < id);
+        ids.insert(at, id);
+        self.positions.insert(id, square);
+    }
+
+    pub(super) fn remove(&mut self, id: u128) -> Option<GridSquare> {
+        let square = self.positions.remove(&id)?;
+        let i = self.index_of(square).unwrap();
+        self.squares[i].retain(|other| *other != id);
+
+        Some(square)
+    }
+
+    pub fn get_position(&self, id: u128) -> Option<GridSquare> {
+        self.positions.get(&id).copied()
+    }
+
+    pub fn positions(&self) -> &HashMap<u128, GridSquare> {
+        &self.positions
+    }
+
+    pub fn get_at(&self, square: GridSquare) -> &[u128] {
+        match self.index_of(square) {
+            Some(i) => &self.squares[i],
+            None => &[],
+        }
+    }
+
+    // Corners are included, and the parts outside the layer are ignored
+    pub fn get_in_rectangle(&self, top_left: GridSquare, bottom_right: GridSquare) -> Vec<u128> {
+        let mut ids: Vec<u128> = Vec::new();
+
+        for y in top_left.y.max(0)..=bottom_right.y.min(self.height - 1) {
+            for x in top_left.x.max(0)..=bottom_right.x.min(self.width - 1) {
+                ids.extend(self.get_at(GridSquare { y, x }));
+            }
+        }
+
+        ids
+    }
+
+    // Same shape as an emanation of the radius
+    pub fn get_in_radius(&self, center: GridSquare, radius: i32) -> Vec<u128> {
+        let mut ids: Vec<u128> = Vec::new();
+
+        for y in (center.y - radius).max(0)..=(center.y + radius).min(self.height - 1) {
+            for x in (center.x - radius).max(0)..=(center.x + radius).min(self.width - 1) {
+                let square = GridSquare { y, x };
+                if gridalgos::distance(center, square) <= radius {
+                    ids.extend(self.get_at(square));
+                }
+            }
+        }
+
+        ids
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_queries() {
+        let mut index = SpatialIndex::new(10, 10);
+        index.insert(3, GridSquare { y: 2, x: 2 });
+        index.insert(1, GridSquare { y: 2, x: 2 });
+        index.insert(2, GridSquare { y: 4, x: 4 });
+        index.insert(4, GridSquare { y: 9, x: 9 });
+
+        assert_eq!(index.get_at(GridSquare { y: 2, x: 2 }), [1, 3]);
+        assert!(index.get_at(GridSquare { y: -1, x: 2 }).is_empty());
+
+        // (4, 4) is 3 squares away from (2, 2) diagonally
+        assert_eq!(index.get_in_radius(GridSquare { y: 2, x: 2 }, 2), [1, 3]);
+        assert_eq!(index.get_in_radius(GridSquare { y: 2, x: 2 }, 3), [1, 3, 2]);
+
+        let top_left = GridSquare { y: -5, x: 3 };
+        let bottom_right = GridSquare { y: 20, x: 20 };
+        assert_eq!(index.get_in_rectangle(top_left, bottom_right), [2, 4]);
+
+        index.insert(3, GridSquare { y: 9, x: 9 });
+        assert_eq!(index.get_at(GridSquare { y: 2, x: 2 }), [1]);
+        assert_eq!(index.get_at(GridSquare { y: 9, x: 9 }), [3, 4]);
+
+        assert_eq!(index.remove(4), Some(GridSquare { y: 9, x: 9 }));
+        assert_eq!(index.remove(4), None);
+        assert_eq!(index.get_position(3), Some(GridSquare { y: 9, x: 9 }));
+        assert_eq!(index.positions().len(), 3);
+    }
+}
@@ -0,0 +1,526 @@
+use crate::ai::{Behavior, AI};
+use crate::constants;
+use crate::creature::bestiary::Bestiary;
+use crate::creature::character;
+use crate::creature::encounter;
+use crate::grid::gridalgos;
+use crate::grid::{DoorState, GridSquare, LeverState, Tile};
+use crate::item::catalog::ItemCatalog;
+use crate::item::generator;
+use crate::player::Player;
+use crate::trap::{Trap, TrapKind};
+use crate::vault::Vaults;
+use crate::world::{Layer, World};
+use rand::prelude::*;
+use rand_chacha::ChaCha20Rng;
+use std::collections::{HashMap, HashSet};
+
+// Probability of a layer getting a vault, if any vault can appear on it
+const VAULT_CHANCE: f64 = 0.5;
+
+pub fn generate_layer(
+    layer_i: i32,
+    party_size: i32,
+    bestiary: &Bestiary,
+    catalog: &ItemCatalog,
+    vaults: &Vaults,
+    rng: &mut ChaCha20Rng,
+) -> Layer {
+    let mut layer = Layer::new(constants::WORLD_HEIGHT, constants::WORLD_WIDTH);
+
+    let vault = vaults
+        .choose(layer_i, rng)
+        .filter(|_| rng.gen_bool(VAULT_CHANCE));
+
+    // The vault gets a room of its own with a ring of floor around it
+    let vault_room_size = vault.map(|v| (v.height() + 2, v.width() + 2));
+    let mut rooms = generate_rooms(&mut layer, vault_room_size, rng);
+    place_doors(&mut layer, &rooms, rng);
+
+    place_terrain(&mut layer, &rooms, rng);
+
+    if let Some(vault) = vault {
+        let room = rooms.remove(0);
+        let top_left = GridSquare {
+            y: room.top + 1,
+            x: room.left + 1,
+        };
+
+        vault.stamp(top_left, layer_i, &mut layer, bestiary, catalog, rng);
+
+        let room_top_left = GridSquare {
+            y: room.top,
+            x: room.left,
+        };
+        let room_bottom_right = GridSquare {
+            y: room.top + room.height - 1,
+            x: room.left + room.width - 1,
+        };
+        layer.vault_area = Some((room_top_left, room_bottom_right));
+    }
+
+    place_traps(&mut layer, layer_i, rng);
+    place_items(&mut layer, layer_i, catalog, rng);
+
+    place_encounters(
+        &mut layer, layer_i, &rooms, party_size, bestiary, catalog, rng,
+    );
+
+    layer
+}
+
+const MAX_ROOMS: usize = 12;
+const ROOM_PLACEMENT_ATTEMPTS: i32 = 100;
+// Walls between two rooms are at least this thick
+const ROOM_SPACING: i32 = 3;
+
+// Rectangular area of floor, not including the surrounding walls
+struct Room {
+    top: i32,
+    left: i32,
+    height: i32,
+    width: i32,
+}
+
+impl Room {
+    fn center(&self) -> GridSquare {
+        GridSquare {
+            y: self.top + self.height / 2,
+            x: self.left + self.width / 2,
+        }
+    }
+
+    fn contains(&self, square: GridSquare) -> bool {
+        square.y >= self.top
+            && square.y < self.top + self.height
+            && square.x >= self.left
+            && square.x < self.left + self.width
+    }
+
+    fn intersects(&self, other: &Room, spacing: i32) -> bool {
+        self.top - spacing < other.top + other.height
+            && other.top - spacing < self.top + self.height
+            && self.left - spacing < other.left + other.width
+            && other.left - spacing < self.left + self.width
+    }
+
+    // Squares of the wall surrounding the room, excluding the corners.
+    // Each square is paired with the direction along the wall.
+    fn get_wall(&self) -> Vec<(GridSquare, GridSquare)> {
+        let mut wall: Vec<(GridSquare, GridSquare)> = Vec::new();
+        let horizontal = GridSquare { y: 0, x: 1 };
+        let vertical = GridSquare { y: 1, x: 0 };
+
+        for x in self.left..(self.left + self.width) {
+            wall.push((GridSquare { y: self.top - 1, x }, horizontal));
+            wall.push((
+                GridSquare {
+                    y: self.top + self.height,
+                    x,
+                },
+                horizontal,
+            ));
+        }
+
+        for y in self.top..(self.top + self.height) {
+            wall.push((
+                GridSquare {
+                    y,
+                    x: self.left - 1,
+                },
+                vertical,
+            ));
+            wall.push((
+                GridSquare {
+                    y,
+                    x: self.left + self.width,
+                },
+                vertical,
+            ));
+        }
+
+        wall
+    }
+}
+
+fn carve(layer: &mut Layer, square: GridSquare) {
+    if layer.grid.get_tile(square) == Some(Tile::Wall) {
+        layer.grid.set_tile(square, Tile::Empty);
+    }
+}
+
+// Carve an L-shaped corridor between the two squares
+fn carve_corridor(layer: &mut Layer, from: GridSquare, to: GridSquare, rng: &mut ChaCha20Rng) {
+    let corner = if rng.gen_bool(0.5) {
+        GridSquare { y: from.y, x: to.x }
+    } else {
+        GridSquare { y: to.y, x: from.x }
+    };
+
+    for (a, b) in [(from, corner), (corner, to)] {
+        for square in gridalgos::line(a, b) {
+            carve(layer, square);
+        }
+    }
+}
+
+// Fill the layer with walls and carve out rooms connected by corridors.
+// If a size is reserved, the first room gets that size.
+fn generate_rooms(
+    layer: &mut Layer,
+    reserved_size: Option<(i32, i32)>,
+    rng: &mut ChaCha20Rng,
+) -> Vec<Room> {
+    let height = layer.grid.height();
+    let width = layer.grid.width();
+
+    for y in 0..height {
+        for x in 0..width {
+            layer.grid.set_tile(GridSquare { y, x }, Tile::Wall);
+        }
+    }
+
+    let mut rooms: Vec<Room> = Vec::new();
+
+    for _ in 0..ROOM_PLACEMENT_ATTEMPTS {
+        if rooms.len() >= MAX_ROOMS {
+            break;
+        }
+
+        let (room_height, room_width) = match reserved_size {
+            Some(size) if rooms.is_empty() => size,
+            _ => (rng.gen_range(4..=9), rng.gen_range(5..=12)),
+        };
+        let room = Room {
+            top: rng.gen_range(1..(height - room_height)),
+            left: rng.gen_range(1..(width - room_width)),
+            height: room_height,
+            width: room_width,
+        };
+
+        if rooms.iter().any(|r| r.intersects(&room, ROOM_SPACING)) {
+            continue;
+        }
+
+        for y in room.top..(room.top + room.height) {
+            for x in room.left..(room.left + room.width) {
+                carve(layer, GridSquare { y, x });
+            }
+        }
+
+        if let Some(prev) = rooms.last() {
+            carve_corridor(layer, prev.center(), room.center(), rng);
+        }
+
+        rooms.push(room);
+    }
+
+    rooms
+}
+
+// Put doors in the openings where corridors enter rooms. Some doors are
+// locked, with a lever to open them placed somewhere it can be reached
+// without passing through the door.
+fn place_doors(layer: &mut Layer, rooms: &[Room], rng: &mut ChaCha20Rng) {
+    let mut doors: Vec<GridSquare> = Vec::new();
+
+    for room in rooms {
+        for (square, along) in room.get_wall() {
+            let before = GridSquare {
+                y: square.y - along.y,
+                x: square.x - along.x,
+            };
+            let after = GridSquare {
+                y: square.y + along.y,
+                x: square.x + along.x,
+            };
+
+            // Only single square wide openings get a door
+            if layer.grid.get_tile(square) == Some(Tile::Empty)
+                && layer.grid.get_tile(before) == Some(Tile::Wall)
+                && layer.grid.get_tile(after) == Some(Tile::Wall)
+                && !doors.contains(&square)
+            {
+                doors.push(square);
+            }
+        }
+    }
+
+    let mut locked: Vec<GridSquare> = Vec::new();
+    for door in &doors {
+        if rng.gen_range(0..8) == 0 {
+            layer.grid.set_tile(*door, Tile::Door(DoorState::Locked));
+            locked.push(*door);
+        }
+    }
+
+    // Other doors are still open floor at this point, so that the
+    // pathfinder only stops at the locked ones
+    if let Some(first_room) = rooms.first() {
+        let max_distance = 3 * layer.grid.height() * layer.grid.width();
+        let paths = gridalgos::find_all_shortest_paths(&[first_room.center()], max_distance, layer);
+        let reachable: HashSet<GridSquare> = gridalgos::get_reachable_squares(&paths)
+            .into_iter()
+            .collect();
+
+        for door in locked {
+            if !place_lever(layer, door, &reachable, rng) {
+                layer.grid.set_tile(door, Tile::Door(DoorState::Closed));
+            }
+        }
+    }
+
+    for door in doors {
+        if layer.grid.get_tile(door) == Some(Tile::Empty) {
+            let state = if rng.gen_range(0..5) == 0 {
+                DoorState::Open
+            } else {
+                DoorState::Closed
+            };
+            layer.grid.set_tile(door, Tile::Door(state));
+        }
+    }
+}
+
+// Returns false if there is no wall next to a reachable square close enough to the door.
+fn place_lever(
+    layer: &mut Layer,
+    door: GridSquare,
+    reachable: &HashSet<GridSquare>,
+    rng: &mut ChaCha20Rng,
+) -> bool {
+    let mut candidates: Vec<GridSquare> = Vec::new();
+
+    for square in gridalgos::emanation(door, constants::LEVER_RADIUS) {
+        if layer.grid.get_tile(square) != Some(Tile::Wall) {
+            continue;
+        }
+
+        let next_to_reachable = gridalgos::get_neighbors(square)
+            .iter()
+            .any(|nb| reachable.contains(nb));
+
+        if next_to_reachable {
+            candidates.push(square);
+        }
+    }
+
+    match candidates.choose(rng) {
+        Some(lever) => {
+            layer.grid.set_tile(*lever, Tile::Lever(LeverState::Up));
+            true
+        }
+        None => false,
+    }
+}
+
+// Scatter patches of rough terrain inside the rooms. Corridors are kept
+// clear so that the rooms stay connected.
+fn place_terrain(layer: &mut Layer, rooms: &[Room], rng: &mut ChaCha20Rng) {
+    let terrain = [
+        Tile::DifficultTerrain,
+        Tile::Water,
+        Tile::Chasm,
+        Tile::Rubble,
+    ];
+
+    for room in rooms {
+        let n_patches = rng.gen_range(0..=2);
+
+        for _ in 0..n_patches {
+            let tile = *terrain.choose(rng).unwrap();
+
+            // A chasm is a single square away from the walls, so that
+            // there is always a way around it
+            let (margin, radius) = match tile {
+                Tile::Chasm => (1, 0),
+                _ => (0, rng.gen_range(1..=2)),
+            };
+
+            if room.height <= 2 * margin || room.width <= 2 * margin {
+                continue;
+            }
+
+            let center = GridSquare {
+                y: rng.gen_range((room.top + margin)..(room.top + room.height - margin)),
+                x: rng.gen_range((room.left + margin)..(room.left + room.width - margin)),
+            };
+
+            for square in gridalgos::emanation(center, radius) {
+                if room.contains(square) && layer.grid.get_tile(square) == Some(Tile::Empty) {
+                    layer.grid.set_tile(square, tile);
+                }
+            }
+        }
+    }
+}
+
+fn place_traps(layer: &mut Layer, layer_i: i32, rng: &mut ChaCha20Rng) {
+    let kinds = TrapKind::all();
+
+    for _ in 0..(3 + layer_i) {
+        let kind = *kinds.choose(rng).unwrap();
+        let squares: Vec<GridSquare> = get_spawn_squares(layer)
+            .into_iter()
+            .filter(|sq| layer.get_trap_at(*sq).is_none())
+            .collect();
+        let position = match squares.choose(rng) {
+            Some(square) => *square,
+            None => break,
+        };
+
+        let (trap, effect) = Trap::new(kind, layer_i, position);
+        layer.add_trap(trap, effect);
+    }
+}
+
+// Items lying around are as strong as the layer is deep
+fn place_items(layer: &mut Layer, layer_i: i32, catalog: &ItemCatalog, rng: &mut ChaCha20Rng) {
+    for _ in 0..(2 + layer_i / 2) {
+        let position = match find_spawn_square(layer, rng) {
+            Some(square) => square,
+            None => break,
+        };
+        let (item, effect) = generator::generate_random(catalog, layer_i, rng);
+
+        let item_id = item.get_id();
+        layer.add_item(item, effect);
+        layer.place_item(item_id, position);
+    }
+}
+
+// Share of the rooms that get an encounter
+const ENCOUNTER_ROOM_SHARE: f64 = 0.6;
+
+// Random free square inside the room
+fn find_room_square(layer: &Layer, room: &Room, rng: &mut ChaCha20Rng) -> Option<GridSquare> {
+    let mut squares: Vec<GridSquare> = Vec::new();
+
+    for y in room.top..(room.top + room.height) {
+        for x in room.left..(room.left + room.width) {
+            let square = GridSquare { y, x };
+            if layer.grid.passable_square(square)
+                && layer.get_living_creatures_at(square).is_empty()
+            {
+                squares.push(square);
+            }
+        }
+    }
+
+    squares.choose(rng).copied()
+}
+
+// Creatures are placed as groups in rooms, each group built from an
+// experience budget for the expected party level at this depth.
+fn place_encounters(
+    layer: &mut Layer,
+    layer_i: i32,
+    rooms: &[Room],
+    party_size: i32,
+    bestiary: &Bestiary,
+    catalog: &ItemCatalog,
+    rng: &mut ChaCha20Rng,
+) {
+    let party_level = encounter::get_party_level(layer_i);
+    let n_encounters = (rooms.len() as f64 * ENCOUNTER_ROOM_SHARE).ceil() as usize;
+
+    for room in rooms.choose_multiple(rng, n_encounters) {
+        let difficulty = encounter::choose_difficulty(rng);
+        let budget = difficulty.get_budget(party_size);
+
+        for member in encounter::build_encounter(bestiary, layer_i, party_level, budget, rng) {
+            let position = match find_room_square(layer, room, rng) {
+                Some(square) => square,
+                None => break,
+            };
+
+            member
+                .definition
+                .spawn(member.level, position, catalog, layer, rng);
+        }
+    }
+}
+
+fn in_vault(layer: &Layer, square: GridSquare) -> bool {
+    match layer.vault_area {
+        Some((top_left, bottom_right)) => {
+            (top_left.y..=bottom_right.y).contains(&square.y)
+                && (top_left.x..=bottom_right.x).contains(&square.x)
+        }
+        None => false,
+    }
+}
+
+// Squares that creatures can stand on and that are not occupied by a
+// creature. The vault keeps to what it was stamped with.
+fn get_spawn_squares(layer: &Layer) -> Vec<GridSquare> {
+    let mut squares: Vec<GridSquare> = Vec::new();
+
+    for y in 0..layer.grid.height() {
+        for x in 0..layer.grid.width() {
+            let square = GridSquare { y, x };
+            if layer.grid.passable_square(square)
+                && layer.get_living_creatures_at(square).is_empty()
+                && !in_vault(layer, square)
+            {
+                squares.push(square);
+            }
+        }
+    }
+
+    squares
+}
+
+// Random spawn square, or None if the layer has no room left.
+pub fn find_spawn_square(layer: &Layer, rng: &mut ChaCha20Rng) -> Option<GridSquare> {
+    get_spawn_squares(layer).choose(rng).copied()
+}
+
+pub fn spawn_player_characters(
+    layer: &mut Layer,
+    players: &HashMap<u128, Player>,
+    catalog: &ItemCatalog,
+    rng: &mut ChaCha20Rng,
+) {
+    for (player_id, player) in players {
+        let position = find_spawn_square(layer, rng)
+            .expect("the layer should have room for every player character");
+
+        let (creature, items) = character::create_character(
+            player.character_name.clone(),
+            player.character,
+            position,
+            catalog,
+        );
+
+        let behavior = Behavior::PlayerControlled(*player_id);
+        let c_ai = AI::new(creature.get_id(), behavior, behavior);
+
+        for (item, effect) in items {
+            layer.add_item(item, effect);
+        }
+
+        layer.add_creature(creature, c_ai);
+    }
+}
+
+// Encounters are balanced for the given number of players
+pub fn generate_world(
+    rng_seed: u64,
+    n_players: usize,
+    bestiary: &Bestiary,
+    catalog: &ItemCatalog,
+    vaults: &Vaults,
+) -> World {
+    let mut rng = ChaCha20Rng::seed_from_u64(rng_seed);
+
+    let mut world = World::new();
+
+    for i in 0..constants::WORLD_LAYERS {
+        let party_size = n_players as i32;
+        let layer = generate_layer(i, party_size, bestiary, catalog, vaults, &mut rng);
+        world.layers.push(layer);
+    }
+
+    world
+}